@@ -0,0 +1,150 @@
+use crate::errors::*;
+use crate::utils::{is_glob, name_key, name_matches};
+use spyparty::Replay;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::iter;
+use std::path::Path;
+
+/// A mapping from user names onto the identity of the player they belong to, so that players
+/// with old accounts or smurfs are treated as a single player.
+///
+/// Aliases are usually loaded from a TOML file, where each key is an identity and its value is
+/// the list of user names belonging to it:
+///
+/// ```toml
+/// plastikqs = ["plastikqs/steam", "plastiksmurf"]
+/// ```
+///
+/// The identity doesn't have to be one of the user names, but it is usually the main account.
+/// User names are matched ignoring case and the "/steam" suffix, the same way as the player
+/// filters.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Aliases {
+    /// The identity of each user name, keyed by the user name as returned by `name_key`.
+    identities: HashMap<String, String>,
+}
+
+impl Aliases {
+    /// Creates an empty set of aliases, which leaves every user name as it is.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Loads aliases from a TOML file.
+    pub fn load(path: &Path) -> Result<Aliases> {
+        let contents = fs::read_to_string(path)
+            .chain_err(|| format!("failed to read aliases file '{}'", path.display()))?;
+
+        Aliases::parse(&contents)
+            .chain_err(|| format!("failed to load aliases file '{}'", path.display()))
+    }
+
+    /// Parses aliases from the contents of a TOML file.
+    pub fn parse(contents: &str) -> Result<Aliases> {
+        let identities: HashMap<String, Vec<String>> =
+            toml::from_str(contents).chain_err(|| "invalid aliases")?;
+
+        Aliases::from_identities(identities)
+    }
+
+    /// Creates aliases from a map of each identity to the user names belonging to it.
+    pub fn from_identities<I, S>(identities: I) -> Result<Aliases>
+    where
+        I: IntoIterator<Item = (S, Vec<S>)>,
+        S: Into<String>,
+    {
+        let mut aliases = Aliases::new();
+
+        for (identity, user_names) in identities {
+            let identity = identity.into();
+
+            for user_name in user_names {
+                aliases.insert(&identity, user_name)?;
+            }
+        }
+
+        Ok(aliases)
+    }
+
+    /// Adds a user name to an identity, failing if it already belongs to a different identity.
+    pub fn insert<S: Into<String>>(&mut self, identity: &str, user_name: S) -> Result<()> {
+        let user_name = user_name.into();
+
+        match self.identities.get(&name_key(&user_name)) {
+            Some(existing) if existing != identity => bail!(
+                "'{}' is an alias of both '{}' and '{}'",
+                user_name,
+                existing,
+                identity
+            ),
+            _ => {
+                self.identities
+                    .insert(name_key(&user_name), identity.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks if there are no aliases at all.
+    pub fn is_empty(&self) -> bool {
+        self.identities.is_empty()
+    }
+
+    /// Gets the identity a user name belongs to, which is the user name itself if it isn't an
+    /// alias of anyone.
+    pub fn identity<'a>(&'a self, user_name: &'a str) -> &'a str {
+        self.identities
+            .get(&name_key(user_name))
+            .map(String::as_str)
+            .unwrap_or(user_name)
+    }
+
+    /// Replaces the user names of the spy and sniper in a replay with their identities.
+    pub fn apply(&self, replay: &mut Replay) {
+        let header = &mut replay.header;
+
+        if let Some(identity) = self.identities.get(&name_key(&header.spy_user_name)) {
+            header.spy_user_name = identity.clone();
+        }
+        if let Some(identity) = self.identities.get(&name_key(&header.sniper_user_name)) {
+            header.sniper_user_name = identity.clone();
+        }
+    }
+
+    /// Gets the identities of every user name matched by a glob pattern (see `name_matches`), in
+    /// alphabetical order.
+    ///
+    /// Aliases are applied as soon as a replay is read, so the user names are matched here
+    /// instead.
+    pub(crate) fn matching_identities<'a>(&'a self, pattern: &str) -> BTreeSet<&'a str> {
+        if !is_glob(pattern) {
+            return BTreeSet::new();
+        }
+
+        self.identities
+            .iter()
+            .filter(|(user_name, _)| name_matches(user_name, pattern))
+            .map(|(_, identity)| identity.as_str())
+            .collect()
+    }
+
+    /// Replaces each name in a list with its identity. Glob patterns are kept for the players
+    /// without aliases, followed by the identities of the user names they match.
+    pub(crate) fn resolve(&self, names: &mut Vec<String>) {
+        let mut resolved: Vec<String> = Vec::with_capacity(names.len());
+
+        for name in names.iter() {
+            let identities = self.matching_identities(name);
+
+            for name in iter::once(self.identity(name)).chain(identities) {
+                if !resolved.iter().any(|other| other == name) {
+                    resolved.push(name.to_string());
+                }
+            }
+        }
+
+        *names = resolved;
+    }
+}
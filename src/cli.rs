@@ -0,0 +1,393 @@
+use crate::config::Config;
+use clap::{App, Arg, ArgMatches};
+use log::warn;
+use spyparsey::errors::*;
+use spyparsey::form::DEFAULT_FORM_GAMES;
+use spyparsey::organize::Action;
+use spyparsey::output::group_by::Dimension;
+use spyparsey::output::lines::LineFormat;
+use spyparsey::output::sheet::Template;
+use spyparsey::output::summary::{SummaryFormat, SummaryOptions};
+use spyparsey::output::timeline::Period;
+use spyparsey::output::{OutputMode, TableFormat};
+use spyparsey::query::{
+    parse_clock, parse_clock_usage, parse_day_of_week, parse_duration, parse_hour_range, parse_map,
+    parse_match_result, parse_mission, parse_mode, parse_player_regex, parse_result, parse_since,
+    parse_until, ValueRange,
+};
+use spyparsey::{filters as query_filters, Aliases, Expression, Index, Query, RatingSystem};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::Path;
+use yaml_rust::Yaml;
+
+/// Adds the filter arguments to the app, along with their "not-" versions.
+pub fn add_filters<'a>(app: App<'a, 'a>, filters: &'a Yaml, negated: &'a [Yaml]) -> App<'a, 'a> {
+    filter_args(filters)
+        .iter()
+        .chain(negated)
+        .fold(app, |app, arg| {
+            app.arg(Arg::from_yaml(arg.as_hash().expect("arguments are maps")))
+        })
+}
+
+/// Builds a "not-" version of each filter argument (e.g. --not-maps for --maps), which excludes the
+/// games that the filter would match. Each takes the same values as its filter, but doesn't
+/// conflict with any other arguments.
+pub fn negated_filters(filters: &Yaml) -> Vec<Yaml> {
+    filter_args(filters).iter().map(negated_filter).collect()
+}
+
+/// Gets the list of filter arguments.
+fn filter_args(filters: &Yaml) -> &[Yaml] {
+    filters["args"]
+        .as_vec()
+        .expect("the filters have a list of arguments")
+}
+
+/// Builds the "not-" version of a single filter argument.
+fn negated_filter(filter: &Yaml) -> Yaml {
+    let (name, settings) = filter
+        .as_hash()
+        .and_then(|filter| filter.iter().next())
+        .expect("arguments are maps from their name to their settings");
+    let name = name.as_str().expect("argument names are strings");
+    let prefixed = |value: &str| Yaml::String(format!("not-{}", value));
+
+    let mut negated = BTreeMap::new();
+
+    for (key, value) in settings.as_hash().expect("argument settings are maps") {
+        let value = match key.as_str() {
+            Some("long") => prefixed(name),
+            Some("help") => Yaml::String(format!("Excludes games matched by --{}", name)),
+            Some("aliases") => match value {
+                Yaml::String(alias) => prefixed(alias),
+                Yaml::Array(aliases) => Yaml::Array(
+                    aliases
+                        .iter()
+                        .filter_map(Yaml::as_str)
+                        .map(prefixed)
+                        .collect(),
+                ),
+                _ => continue,
+            },
+            Some("long_help") | Some("conflicts_with") => continue,
+            _ => value.clone(),
+        };
+
+        negated.insert(key.clone(), value);
+    }
+
+    let mut arg = BTreeMap::new();
+    arg.insert(prefixed(name), Yaml::Hash(negated));
+    Yaml::Hash(arg)
+}
+
+/// Gets the names of the subcommands of the app.
+pub fn subcommand_names(yaml: &Yaml) -> Vec<&str> {
+    yaml["subcommands"]
+        .as_vec()
+        .map(|subcommands| {
+            subcommands
+                .iter()
+                .filter_map(|subcommand| subcommand.as_hash()?.keys().next()?.as_str())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Moves a subcommand and the arguments after it in front of the other arguments, so that it
+/// isn't taken as another value of a list such as --players or --paths passed before it. A
+/// subcommand name straight after an option is left where it is, as it's the value of that
+/// option. Every argument is global, so the ones before the subcommand mean the same after it.
+pub fn subcommand_first(mut args: Vec<OsString>, subcommands: &[&str]) -> Vec<OsString> {
+    let is_option = |arg: &OsString| arg.to_string_lossy().starts_with('-');
+    let position = (0..args.len()).take_while(|&i| args[i] != "--").find(|&i| {
+        (i == 0 || !is_option(&args[i - 1])) && subcommands.iter().any(|&name| args[i] == name)
+    });
+
+    if let Some(position) = position {
+        let mut reordered = args.split_off(position);
+        reordered.append(&mut args);
+        args = reordered;
+    }
+
+    args
+}
+
+/// Builds a query from the filters passed on the command line.
+///
+/// Any filter can also be passed with a "not-" prefix (e.g. --not-maps), which builds the excluded
+/// part of the query from those arguments instead. Aliases from --aliases replace any aliases from
+/// the config.
+pub fn query(matches: &ArgMatches, config: &Config) -> Result<Query> {
+    let mut query = filters(matches, "")?;
+    let exclude = filters(matches, "not-")?;
+
+    if query_filters::is_active(&exclude) {
+        query.exclude = Some(Box::new(exclude));
+    }
+
+    query.match_result = parse_one(matches, "match-result", parse_match_result)?;
+
+    // The score of each match is shown, so none of its games can be left out
+    if let Ok(OutputMode::Matches(_)) = output_mode(matches) {
+        query.whole_matches = true;
+    }
+
+    if let Some(scout) = matches.subcommand_matches("scout") {
+        if query.has_player_filters() {
+            bail!("scout can't be combined with --players, --pair, --spies, --snipers or --players-regex");
+        }
+
+        query = query.players(scout.value_of("player"));
+    }
+
+    let aliases = match matches.value_of("aliases") {
+        Some(path) => Aliases::load(Path::new(path))?,
+        None => config.aliases()?,
+    };
+    if !aliases.is_empty() {
+        query = query.aliases(aliases);
+    }
+
+    if query.match_result.is_some() && query.match_player().is_none() {
+        bail!("--match-result requires a player to be passed with --players or --pair");
+    }
+
+    Ok(query)
+}
+
+/// Builds a query from the filter arguments which start with a prefix.
+fn filters(matches: &ArgMatches, prefix: &str) -> Result<Query> {
+    let arg = |name: &str| format!("{}{}", prefix, name);
+
+    Ok(Query {
+        players: strings(matches, &arg("players")),
+        pair: strings(matches, &arg("pair")),
+        spies: strings(matches, &arg("spies")),
+        snipers: strings(matches, &arg("snipers")),
+        players_regex: parse_all(matches, &arg("players-regex"), parse_player_regex)?,
+        maps: parse_all(matches, &arg("maps"), parse_map)?,
+        modes: parse_all(matches, &arg("modes"), parse_mode)?,
+        results: parse_all(matches, &arg("results"), parse_result)?,
+        completed_missions: parse_all(matches, &arg("completed-missions"), parse_mission)?,
+        completed_missions_all: parse_all(matches, &arg("completed-missions-all"), parse_mission)?,
+        spy_win: matches.is_present(arg("spywin")),
+        sniper_win: matches.is_present(arg("sniperwin")),
+        countdown: matches.is_present(arg("countdown")),
+        since: parse_one(matches, &arg("since"), parse_since)?,
+        until: parse_one(matches, &arg("until"), parse_until)?,
+        days_of_week: parse_all(matches, &arg("on-day-of-week"), parse_day_of_week)?,
+        hour_range: parse_one(matches, &arg("hour-range"), parse_hour_range)?,
+        duration: duration(matches, prefix)?,
+        clock: parse_one(matches, &arg("clock"), parse_clock)?,
+        clock_usage: parse_one(matches, &arg("clock-usage"), parse_clock_usage)?,
+        expression: parse_one(matches, &arg("where"), Expression::parse)?,
+        exclude: None,
+        match_result: None,
+        whole_matches: false,
+        aliases: Default::default(),
+    })
+}
+
+/// Combines the minimum and maximum duration arguments into a single range.
+fn duration(matches: &ArgMatches, prefix: &str) -> Result<Option<ValueRange<f32>>> {
+    let min = parse_one(matches, &format!("{}min-duration", prefix), parse_duration)?;
+    let max = parse_one(matches, &format!("{}max-duration", prefix), parse_duration)?;
+
+    match (min, max) {
+        (None, None) => Ok(None),
+        (Some(min), Some(max)) if min > max => {
+            bail!("--min-duration can't be longer than --max-duration")
+        }
+        (min, max) => Ok(Some(ValueRange { min, max })),
+    }
+}
+
+/// Chooses how the matched replays are shown, based on the subcommand. Showing a summary is the
+/// default when there is no subcommand.
+pub fn output_mode(matches: &ArgMatches) -> Result<OutputMode> {
+    let output_mode = match matches.subcommand() {
+        ("query", Some(query)) if query.is_present("count") => OutputMode::Count,
+        ("query", Some(query)) => match query.value_of("format") {
+            Some(template) => OutputMode::Lines(LineFormat::parse(template)?),
+            None => OutputMode::Paths,
+        },
+        ("export", Some(export)) => export_mode(export)?,
+        ("stats", Some(stats)) => stats_mode(stats)?,
+        ("scout", Some(scout)) => OutputMode::Scout(summary_format(scout)),
+        ("summary", Some(summary)) => {
+            OutputMode::Summary(summary_format(summary), summary_options(summary)?)
+        }
+        (name, _) => bail!("{} doesn't show the matched replays", name),
+    };
+
+    Ok(output_mode)
+}
+
+/// Chooses the format replays are exported in, loading the template of the sheet format.
+fn export_mode(matches: &ArgMatches) -> Result<OutputMode> {
+    let format = matches.value_of("format");
+
+    if format != Some("sheet") && matches.is_present("template") {
+        bail!("--template can only be used with --format sheet");
+    }
+
+    let output_mode = match format {
+        Some("json") => OutputMode::Json,
+        Some("ndjson") => OutputMode::Ndjson,
+        Some("sheet") => match matches.value_of("template") {
+            Some(path) => OutputMode::Sheet(Template::load(Path::new(path))?),
+            None => OutputMode::Sheet(Template::default()),
+        },
+        _ => OutputMode::Csv,
+    };
+
+    Ok(output_mode)
+}
+
+/// Chooses which table of stats is shown, based on the subcommand of stats.
+fn stats_mode(matches: &ArgMatches) -> Result<OutputMode> {
+    let output_mode = match matches.subcommand() {
+        ("head-to-head", Some(head_to_head)) => {
+            if !head_to_head.is_present("players") {
+                bail!("head-to-head requires the players to be passed with --players");
+            }
+
+            OutputMode::HeadToHead(table_format(head_to_head))
+        }
+        ("group-by", Some(group_by)) => {
+            let dimensions = parse_all(group_by, "dimensions", Dimension::parse)?;
+
+            OutputMode::GroupBy(dimensions, table_format(group_by))
+        }
+        ("matches", Some(matches)) => OutputMode::Matches(table_format(matches)),
+        ("form", Some(form)) => {
+            let games = parse_one(form, "games", parse_count)?;
+
+            OutputMode::Form(games.unwrap_or(DEFAULT_FORM_GAMES), table_format(form))
+        }
+        ("timeline", Some(timeline)) => {
+            let period = Period::parse(timeline.value_of("period").unwrap())?;
+
+            OutputMode::Timeline(period, table_format(timeline))
+        }
+        ("ratings", Some(ratings)) => {
+            let system = match ratings.value_of("system") {
+                Some("glicko2") => RatingSystem::Glicko2,
+                _ => RatingSystem::Elo,
+            };
+
+            if ratings.is_present("history") {
+                OutputMode::RatingHistory(system)
+            } else {
+                OutputMode::Ratings(system, table_format(ratings))
+            }
+        }
+        _ => bail!("stats requires a subcommand"),
+    };
+
+    Ok(output_mode)
+}
+
+/// Gets the dimensions and action of the organize subcommand.
+pub fn organize(matches: &ArgMatches) -> Result<(Vec<Dimension>, Action)> {
+    let dimensions = parse_all(matches, "by", Dimension::parse)?;
+    let action = if matches.is_present("dry-run") {
+        Action::DryRun
+    } else if matches.is_present("move") {
+        Action::Move
+    } else {
+        Action::Copy
+    };
+
+    Ok((dimensions, action))
+}
+
+/// Gets the format to show the summary (or any other report shown like a summary) in.
+fn summary_format(matches: &ArgMatches) -> SummaryFormat {
+    match matches.value_of("format") {
+        Some("json") => SummaryFormat::Json,
+        Some("yaml") => SummaryFormat::Yaml,
+        Some("markdown") => SummaryFormat::Markdown,
+        _ => SummaryFormat::Text,
+    }
+}
+
+/// Gets the options which change how much of the summary is shown.
+fn summary_options(matches: &ArgMatches) -> Result<SummaryOptions> {
+    let mut options = SummaryOptions::default();
+
+    if let Some(mission_sets) = parse_one(matches, "mission-sets", parse_count)? {
+        options.mission_sets = mission_sets;
+    }
+    options.detailed = matches.is_present("detailed");
+
+    Ok(options)
+}
+
+/// Gets the format to show tables in.
+fn table_format(matches: &ArgMatches) -> TableFormat {
+    match matches.value_of("table-format") {
+        Some("csv") => TableFormat::Csv,
+        _ => TableFormat::Text,
+    }
+}
+
+/// Opens the index of parsed replays, unless caching has been turned off.
+pub fn index(matches: &ArgMatches) -> Option<Index> {
+    if matches.is_present("no-cache") {
+        return None;
+    }
+
+    let rebuild = matches.is_present("rebuild-index")
+        || matches!(
+            matches.subcommand_matches("index"),
+            Some(index) if index.is_present("rebuild")
+        );
+
+    match Index::default_path() {
+        Some(path) if rebuild => Some(Index::empty(&path)),
+        Some(path) => Some(Index::load(&path)),
+        None => {
+            warn!("could not locate a cache directory for the index");
+            None
+        }
+    }
+}
+
+/// Collects all the values of an argument as owned strings.
+fn strings(matches: &ArgMatches, arg: &str) -> Vec<String> {
+    matches
+        .values_of(arg)
+        .map(|values| values.map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Parses all the values of an argument, failing on the first invalid value.
+fn parse_all<T, F>(matches: &ArgMatches, arg: &str, parse: F) -> Result<Vec<T>>
+where
+    F: Fn(&str) -> Result<T>,
+{
+    matches
+        .values_of(arg)
+        .map(|values| values.map(parse).collect())
+        .unwrap_or_else(|| Ok(vec![]))
+}
+
+/// Parses the single value of an argument, if it is present.
+fn parse_one<T, F>(matches: &ArgMatches, arg: &str, parse: F) -> Result<Option<T>>
+where
+    F: Fn(&str) -> Result<T>,
+{
+    matches.value_of(arg).map(parse).transpose()
+}
+
+/// Parses a number of things to show, such as "10".
+fn parse_count(value: &str) -> Result<usize> {
+    match value.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => bail!("'{}' is not a valid number", value),
+    }
+}
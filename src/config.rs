@@ -0,0 +1,166 @@
+use clap::ArgMatches;
+use serde::Deserialize;
+use spyparsey::errors::*;
+use spyparsey::Aliases;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Settings loaded from a TOML config file, which fill in arguments that aren't passed on the
+/// command line:
+///
+/// ```toml
+/// paths = ["~/spyparty/replays"]
+/// command = ["stats", "matches"]
+/// summary-format = "markdown"
+///
+/// [aliases]
+/// plastikqs = ["plastikqs/steam", "plastiksmurf"]
+///
+/// [queries]
+/// weekly-league = ["--on-day-of-week", "friday", "--hour-range", "20..23"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Directories to look for replays in when --paths isn't passed.
+    paths: Vec<String>,
+    /// The subcommand and its arguments to use when no subcommand is passed.
+    command: Vec<String>,
+    /// The format of the summary and scouting reports when --format isn't passed.
+    summary_format: Option<String>,
+    /// The format of stats tables when --table-format isn't passed.
+    table_format: Option<String>,
+    /// The template of the sheet export when --template isn't passed.
+    sheet_template: Option<String>,
+    /// Player aliases, in the same format as an --aliases file.
+    aliases: HashMap<String, Vec<String>>,
+    /// Saved lists of arguments, used with --query NAME.
+    queries: HashMap<String, Vec<String>>,
+}
+
+impl Config {
+    /// Gets the default location of the config file, inside the user's config directory (e.g.
+    /// `$XDG_CONFIG_HOME/spyparsey/config.toml`).
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|mut path| {
+            path.push("spyparsey");
+            path.push("config.toml");
+            path
+        })
+    }
+
+    /// Loads the config file passed with --config, or the default config file if it exists.
+    pub fn load(matches: &ArgMatches) -> Result<Config> {
+        match matches.value_of("config") {
+            Some(path) => Config::from_file(Path::new(path)),
+            None => match Config::default_path() {
+                Some(path) if path.is_file() => Config::from_file(&path),
+                _ => Ok(Default::default()),
+            },
+        }
+    }
+
+    /// Loads a config from a TOML file.
+    fn from_file(path: &Path) -> Result<Config> {
+        let contents = fs::read_to_string(path)
+            .chain_err(|| format!("failed to read config file '{}'", path.display()))?;
+        toml::from_str(&contents).chain_err(|| format!("invalid config file '{}'", path.display()))
+    }
+
+    /// Gets the arguments of the saved query passed with --query, if any.
+    pub fn query_args(&self, matches: &ArgMatches) -> Result<Vec<String>> {
+        let name = match matches.value_of("query") {
+            Some(name) => name,
+            None => return Ok(vec![]),
+        };
+
+        match self.queries.get(name) {
+            Some(args) => Ok(args.clone()),
+            None => {
+                let mut names: Vec<&str> = self.queries.keys().map(String::as_str).collect();
+                names.sort();
+
+                if names.is_empty() {
+                    bail!("there is no saved query called '{}' in the config", name);
+                }
+                bail!(
+                    "there is no saved query called '{}' in the config (expected one of {})",
+                    name,
+                    names.join(", ")
+                );
+            }
+        }
+    }
+
+    /// Gets the subcommand to insert before the arguments when none was passed, which is the
+    /// command from the config or the summary.
+    pub fn command(&self, matches: &ArgMatches) -> Vec<String> {
+        if matches.subcommand_name().is_some() {
+            vec![]
+        } else if self.command.is_empty() {
+            vec!["summary".to_string()]
+        } else {
+            self.command.clone()
+        }
+    }
+
+    /// Gets the arguments for each default in the config which wasn't overridden on the command
+    /// line.
+    pub fn default_args(&self, matches: &ArgMatches) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if !matches.is_present("paths") && !self.paths.is_empty() {
+            args.push("--paths".to_string());
+            args.extend(self.paths.iter().map(|path| expand_home(path)));
+        }
+
+        // The formats and templates are arguments of the innermost subcommand
+        let (name, leaf) = leaf_subcommand(matches);
+        let format = match name {
+            "summary" | "scout" => self
+                .summary_format
+                .as_ref()
+                .filter(|_| !leaf.is_present("format"))
+                .map(|format| format!("--format={}", format)),
+            "head-to-head" | "group-by" | "matches" | "form" | "timeline" | "ratings" => self
+                .table_format
+                .as_ref()
+                .filter(|_| !leaf.is_present("table-format"))
+                .map(|format| format!("--table-format={}", format)),
+            "export" if leaf.value_of("format") == Some("sheet") => self
+                .sheet_template
+                .as_ref()
+                .filter(|_| !leaf.is_present("template"))
+                .map(|path| format!("--template={}", expand_home(path))),
+            _ => None,
+        };
+        args.extend(format);
+
+        args
+    }
+
+    /// Gets the aliases from the config.
+    pub fn aliases(&self) -> Result<Aliases> {
+        Aliases::from_identities(self.aliases.clone()).chain_err(|| "invalid aliases in config")
+    }
+}
+
+/// Expands a leading "~" in a path to the user's home directory.
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).display().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Gets the name and arguments of the innermost subcommand that was passed.
+fn leaf_subcommand<'a, 'b>(matches: &'a ArgMatches<'b>) -> (&'a str, &'a ArgMatches<'b>) {
+    let mut leaf = ("", matches);
+
+    while let (name, Some(matches)) = leaf.1.subcommand() {
+        leaf = (name, matches);
+    }
+
+    leaf
+}
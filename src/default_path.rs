@@ -0,0 +1,147 @@
+use log::debug;
+use spyparsey::errors::*;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// The Steam app ID of SpyParty, used for its Proton prefix.
+#[cfg(not(windows))]
+const SPYPARTY_APP_ID: &str = "329070";
+
+/// The environment variable which overrides the location of the SpyParty replays directory.
+const REPLAYS_VAR: &str = "SPYPARSEY_REPLAYS";
+
+/// Finds the SpyParty replays directory, either from the `SPYPARSEY_REPLAYS` environment variable
+/// or by searching the places SpyParty stores its replays on this platform.
+pub fn find() -> Result<PathBuf> {
+    if let Some(path) = env::var_os(REPLAYS_VAR) {
+        let path = PathBuf::from(path);
+
+        if path.is_dir() {
+            return Ok(path);
+        } else {
+            bail!(
+                "{} is set to '{}', which is not a directory",
+                REPLAYS_VAR,
+                path.display()
+            );
+        }
+    }
+
+    let candidates = candidates();
+
+    for path in &candidates {
+        debug!("looking for replays in '{}'", path.display());
+
+        if path.is_dir() {
+            return Ok(path.clone());
+        }
+    }
+
+    if candidates.is_empty() {
+        bail!("cannot find SpyParty replays directory (could not find anywhere to look)");
+    }
+
+    let tried: Vec<String> = candidates
+        .iter()
+        .map(|path| format!("    {}", path.display()))
+        .collect();
+    bail!(
+        "cannot find SpyParty replays directory, tried:\n{}",
+        tried.join("\n")
+    );
+}
+
+/// Gets the places the replays directory could be on Windows, which is just inside the local
+/// application data.
+#[cfg(windows)]
+fn candidates() -> Vec<PathBuf> {
+    match env::var_os("LOCALAPPDATA") {
+        Some(app_data) => vec![replays_in(&PathBuf::from(app_data))],
+        None => vec![],
+    }
+}
+
+/// Gets the places the replays directory could be on other platforms, where SpyParty runs under
+/// Proton or Wine. Every Steam compatdata prefix is checked (as SpyParty could have been added as
+/// a non-Steam game, which gets a random app ID), followed by the Wine prefixes.
+#[cfg(not(windows))]
+fn candidates() -> Vec<PathBuf> {
+    // Proton always uses "steamuser", but Wine uses the name of the Linux user
+    let mut prefixes: Vec<(PathBuf, String)> = Vec::new();
+    let wine_user = env::var("USER").unwrap_or_else(|_| "steamuser".to_string());
+
+    if let Some(home) = dirs::home_dir() {
+        let mut steam_roots: Vec<PathBuf> = Vec::new();
+
+        for root in &[
+            ".steam/steam",
+            ".local/share/Steam",
+            ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+        ] {
+            // ~/.steam/steam is usually a link to one of the other roots
+            let root = home.join(root);
+            let root = root.canonicalize().unwrap_or(root);
+
+            if !steam_roots.contains(&root) {
+                steam_roots.push(root);
+            }
+        }
+
+        for root in steam_roots {
+            let compat_data = root.join("steamapps").join("compatdata");
+            let mut found = sorted_dirs(&compat_data);
+
+            if found.is_empty() {
+                found.push(compat_data.join(SPYPARTY_APP_ID));
+            }
+
+            for prefix in found {
+                prefixes.push((prefix.join("pfx"), "steamuser".to_string()));
+            }
+        }
+
+        if let Some(prefix) = env::var_os("WINEPREFIX") {
+            prefixes.push((PathBuf::from(prefix), wine_user.clone()));
+        }
+        prefixes.push((home.join(".wine"), wine_user));
+    }
+
+    let mut candidates = Vec::new();
+
+    for (prefix, default_user) in prefixes {
+        let users_dir = prefix.join("drive_c").join("users");
+        let mut users = sorted_dirs(&users_dir);
+
+        if users.is_empty() {
+            users.push(users_dir.join(default_user));
+        }
+
+        for user in users {
+            candidates.push(replays_in(&user.join("AppData").join("Local")));
+        }
+    }
+
+    candidates
+}
+
+/// Gets the subdirectories of a directory in alphabetical order, or nothing if the directory
+/// can't be read.
+#[cfg(not(windows))]
+fn sorted_dirs(path: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = match path.read_dir() {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(_) => vec![],
+    };
+
+    dirs.sort();
+    dirs
+}
+
+/// Gets the replays directory inside a local application data directory.
+fn replays_in(app_data: &Path) -> PathBuf {
+    app_data.join("SpyParty").join("replays")
+}
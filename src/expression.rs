@@ -0,0 +1,365 @@
+use crate::aliases::Aliases;
+use crate::errors::*;
+use crate::filters::{
+    Clock, ClockUsage, CompletedMissions, Countdown, DaysOfWeek, GameDuration, GameModes,
+    HourRange, Maps, Players, Results, Since, SniperWin, Snipers, Spies, SpyWin, Until,
+};
+use crate::query::{
+    parse_clock, parse_clock_usage, parse_day_of_week, parse_duration_range, parse_hour_range,
+    parse_map, parse_mission, parse_mode, parse_result, parse_since, parse_until, ModeFilter,
+    ValueRange,
+};
+use chrono::{DateTime, Local, Weekday};
+use spyparty::{GameResult, Map, Mission, Replay};
+use std::fmt;
+
+/// A boolean expression of conditions on a replay, for example
+/// `spy:checker and (map:balcony or map:ballroom) and not result:timeout`.
+///
+/// Each condition is a `key:value` pair (values containing spaces can be quoted) or a single
+/// keyword, and they can be combined with `and`, `or`, `not` and parentheses. `not` binds the
+/// tightest, followed by `and` and then `or`.
+pub enum Expression {
+    /// Both expressions must be true.
+    And(Box<Expression>, Box<Expression>),
+    /// Either expression must be true.
+    Or(Box<Expression>, Box<Expression>),
+    /// The expression must be false.
+    Not(Box<Expression>),
+    /// A single condition must be true.
+    Condition(Condition),
+}
+
+/// A single condition on a replay, each of which behaves the same as its equivalent filter.
+pub enum Condition {
+    /// `player:NAME`, the player is either the spy or the sniper.
+    Player(String),
+    /// `spy:NAME`, the player is the spy.
+    Spy(String),
+    /// `sniper:NAME`, the player is the sniper.
+    Sniper(String),
+    /// `map:MAP`, the game was played on the map.
+    Map(Map),
+    /// `mode:MODE`, the game was played in the mode.
+    Mode(ModeFilter),
+    /// `result:RESULT`, the game ended in the result.
+    Result(GameResult),
+    /// `mission:MISSION`, the mission was completed.
+    Mission(Mission),
+    /// `spywin`, the game ended in a spy win.
+    SpyWin,
+    /// `sniperwin`, the game ended in a sniper win.
+    SniperWin,
+    /// `countdown`, the game ended with the mission win countdown.
+    Countdown,
+    /// `since:TIME`, the game started at or after the time.
+    Since(DateTime<Local>),
+    /// `until:TIME`, the game started on or before the time.
+    Until(DateTime<Local>),
+    /// `day:DAY`, the game started on the day of the week.
+    Day(Weekday),
+    /// `hour:HOURS`, the game started within the range of hours.
+    Hour((u32, u32)),
+    /// `duration:DURATIONS`, the length of the game was within the range.
+    Duration(ValueRange<f32>),
+    /// `clock:SECONDS`, the starting clock was within the range.
+    Clock(ValueRange<u32>),
+    /// `clock-usage:FRACTION`, the fraction of the clock used was within the range.
+    ClockUsage(ValueRange<f32>),
+}
+
+impl Expression {
+    /// Parses an expression from a string.
+    pub fn parse(input: &str) -> Result<Expression> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+            depth: 0,
+        };
+        let expression = parser.parse_or()?;
+
+        if let Some(token) = parser.peek() {
+            bail!("unexpected {} in expression", token);
+        }
+
+        Ok(expression)
+    }
+
+    /// Resolves the player names in every condition to their identities.
+    pub(crate) fn resolve_aliases(&mut self, aliases: &Aliases) {
+        match self {
+            Expression::And(left, right) | Expression::Or(left, right) => {
+                left.resolve_aliases(aliases);
+                right.resolve_aliases(aliases);
+            }
+            Expression::Not(inner) => inner.resolve_aliases(aliases),
+            Expression::Condition(condition) => {
+                let (name, condition_for): (&str, fn(String) -> Condition) = match condition {
+                    Condition::Player(name) => (name, Condition::Player),
+                    Condition::Spy(name) => (name, Condition::Spy),
+                    Condition::Sniper(name) => (name, Condition::Sniper),
+                    _ => return,
+                };
+                let mut names = vec![name.to_string()];
+                aliases.resolve(&mut names);
+
+                // A glob pattern also matches the identities of the user names it matches
+                if let Some(expression) = names
+                    .into_iter()
+                    .map(|name| Expression::Condition(condition_for(name)))
+                    .reduce(|left, right| Expression::Or(Box::new(left), Box::new(right)))
+                {
+                    *self = expression;
+                }
+            }
+        }
+    }
+
+    /// Checks if a replay satisfies the expression.
+    pub fn evaluate(&self, replay: &Replay) -> bool {
+        match self {
+            Expression::And(left, right) => left.evaluate(replay) && right.evaluate(replay),
+            Expression::Or(left, right) => left.evaluate(replay) || right.evaluate(replay),
+            Expression::Not(inner) => !inner.evaluate(replay),
+            Expression::Condition(condition) => condition.evaluate(replay),
+        }
+    }
+}
+
+impl Condition {
+    /// Parses a condition from a single `key:value` word or keyword.
+    fn parse(word: &str) -> Result<Condition> {
+        let (key, value) = match word.find(':') {
+            Some(index) => (&word[..index], Some(&word[index + 1..])),
+            None => (word, None),
+        };
+        let key = key.to_ascii_lowercase();
+        let required = || match value {
+            Some(value) => Ok(value),
+            None => Err(Error::from(format!(
+                "'{}' in expression requires a value",
+                key
+            ))),
+        };
+
+        let condition = match key.as_str() {
+            "player" | "players" => Condition::Player(required()?.to_string()),
+            "spy" | "spies" => Condition::Spy(required()?.to_string()),
+            "sniper" | "snipers" => Condition::Sniper(required()?.to_string()),
+            "map" | "maps" => Condition::Map(parse_map(required()?)?),
+            "mode" | "modes" => Condition::Mode(parse_mode(required()?)?),
+            "result" | "results" => Condition::Result(parse_result(required()?)?),
+            "mission" | "missions" => Condition::Mission(parse_mission(required()?)?),
+            "since" => Condition::Since(parse_since(required()?)?),
+            "until" => Condition::Until(parse_until(required()?)?),
+            "day" => Condition::Day(parse_day_of_week(required()?)?),
+            "hour" => Condition::Hour(parse_hour_range(required()?)?),
+            "duration" => Condition::Duration(parse_duration_range(required()?)?),
+            "clock" => Condition::Clock(parse_clock(required()?)?),
+            "clock-usage" => Condition::ClockUsage(parse_clock_usage(required()?)?),
+            "spywin" if value.is_none() => Condition::SpyWin,
+            "sniperwin" if value.is_none() => Condition::SniperWin,
+            "countdown" if value.is_none() => Condition::Countdown,
+            _ => bail!("'{}' is not a valid condition in expression", word),
+        };
+
+        Ok(condition)
+    }
+
+    /// Checks if a replay satisfies the condition.
+    fn evaluate(&self, replay: &Replay) -> bool {
+        match self {
+            Condition::Player(name) => Players::predicate(name, replay),
+            Condition::Spy(name) => Spies::predicate(name, replay),
+            Condition::Sniper(name) => Snipers::predicate(name, replay),
+            Condition::Map(map) => Maps::predicate(map, replay),
+            Condition::Mode(mode) => GameModes::predicate(mode, replay),
+            Condition::Result(result) => Results::predicate(result, replay),
+            Condition::Mission(mission) => CompletedMissions::predicate(mission, replay),
+            Condition::SpyWin => SpyWin::predicate(replay),
+            Condition::SniperWin => SniperWin::predicate(replay),
+            Condition::Countdown => Countdown::predicate(replay),
+            Condition::Since(since) => Since::predicate(since, replay),
+            Condition::Until(until) => Until::predicate(until, replay),
+            Condition::Day(day) => DaysOfWeek::predicate(day, replay),
+            Condition::Hour(hours) => HourRange::predicate(hours, replay),
+            Condition::Duration(duration) => GameDuration::predicate(duration, replay),
+            Condition::Clock(clock) => Clock::predicate(clock, replay),
+            Condition::ClockUsage(usage) => ClockUsage::predicate(usage, replay),
+        }
+    }
+}
+
+/// A token of an expression.
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Word(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::And => write!(f, "'and'"),
+            Token::Or => write!(f, "'or'"),
+            Token::Not => write!(f, "'not'"),
+            Token::Word(word) => write!(f, "'{}'", word),
+        }
+    }
+}
+
+/// Splits an expression into tokens.
+///
+/// Words are separated by whitespace or parentheses, and any part of a word can be quoted with
+/// single or double quotes to include those characters. Quoted keywords are treated as words.
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            tokens.push(Token::Open);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(Token::Close);
+        } else {
+            let mut word = String::new();
+            let mut quoted = false;
+
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '(' || c == ')' {
+                    break;
+                }
+
+                chars.next();
+
+                if c == '"' || c == '\'' {
+                    quoted = true;
+
+                    loop {
+                        match chars.next() {
+                            Some(next) if next == c => break,
+                            Some(next) => word.push(next),
+                            None => bail!("unterminated quote in expression"),
+                        }
+                    }
+                } else {
+                    word.push(c);
+                }
+            }
+
+            tokens.push(match word.to_ascii_lowercase().as_str() {
+                "and" if !quoted => Token::And,
+                "or" if !quoted => Token::Or,
+                "not" if !quoted => Token::Not,
+                _ => Token::Word(word),
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// The deepest that `not`s and parentheses can be nested in an expression, so that parsing and
+/// matching it can't overflow the stack.
+const MAX_DEPTH: usize = 100;
+
+/// A recursive descent parser over the tokens of an expression.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    /// Looks at the next token without consuming it.
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Goes one level deeper into a nested expression, failing if it is nested too deeply.
+    fn descend(&mut self) -> Result<()> {
+        self.depth += 1;
+
+        if self.depth > MAX_DEPTH {
+            bail!("expression is nested more than {} levels deep", MAX_DEPTH);
+        }
+
+        Ok(())
+    }
+
+    /// Parses expressions separated by `or`.
+    fn parse_or(&mut self) -> Result<Expression> {
+        let mut expression = self.parse_and()?;
+
+        while let Some(Token::Or) = self.peek() {
+            self.position += 1;
+            expression = Expression::Or(Box::new(expression), Box::new(self.parse_and()?));
+        }
+
+        Ok(expression)
+    }
+
+    /// Parses expressions separated by `and`.
+    fn parse_and(&mut self) -> Result<Expression> {
+        let mut expression = self.parse_not()?;
+
+        while let Some(Token::And) = self.peek() {
+            self.position += 1;
+            expression = Expression::And(Box::new(expression), Box::new(self.parse_not()?));
+        }
+
+        Ok(expression)
+    }
+
+    /// Parses an expression optionally preceded by `not`.
+    fn parse_not(&mut self) -> Result<Expression> {
+        if let Some(Token::Not) = self.peek() {
+            self.position += 1;
+            self.descend()?;
+            let expression = self.parse_not()?;
+            self.depth -= 1;
+            Ok(Expression::Not(Box::new(expression)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    /// Parses either a parenthesised expression or a single condition.
+    fn parse_primary(&mut self) -> Result<Expression> {
+        let position = self.position;
+
+        if position >= self.tokens.len() {
+            bail!("unexpected end of expression");
+        }
+
+        self.position += 1;
+
+        match &self.tokens[position] {
+            Token::Open => {
+                self.descend()?;
+                let expression = self.parse_or()?;
+                self.depth -= 1;
+
+                match self.peek() {
+                    Some(Token::Close) => {
+                        self.position += 1;
+                        Ok(expression)
+                    }
+                    Some(token) => bail!("expected ')' but found {} in expression", token),
+                    None => bail!("expected ')' at end of expression"),
+                }
+            }
+            Token::Word(word) => Ok(Expression::Condition(Condition::parse(word)?)),
+            token => bail!("unexpected {} in expression", token),
+        }
+    }
+}
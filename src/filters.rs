@@ -1,22 +1,28 @@
-use crate::errors::*;
-use clap::ArgMatches;
+use crate::query::Query;
 use spyparty::Replay;
 
 /// Trait to be used by filters on replays.
 pub trait Filter {
-    fn filter(&self, replay: &Replay, matches: &ArgMatches) -> bool;
+    /// Checks if the query uses this filter at all.
+    fn is_active(&self, query: &Query) -> bool;
+    /// Checks if a replay is matched by this filter (always true if the filter isn't active).
+    fn filter(&self, replay: &Replay, query: &Query) -> bool;
 }
 
 // Macros have to come before the separate filter modules!
 
 /// Macro to create a very simple OR-type filter for multiple values.
 macro_rules! basic_or {
-    ($arg:expr, $pred:path) => {
-        fn filter(&self, replay: &Replay, matches: &ArgMatches) -> bool {
-            if let Some(mut values) = matches.values_of($arg) {
-                values.any(|p| $pred(p, replay))
-            } else {
+    ($field:ident, $pred:path) => {
+        fn is_active(&self, query: &Query) -> bool {
+            !query.$field.is_empty()
+        }
+
+        fn filter(&self, replay: &Replay, query: &Query) -> bool {
+            if query.$field.is_empty() {
                 true
+            } else {
+                query.$field.iter().any(|p| $pred(p, replay))
             }
         }
     };
@@ -24,12 +30,16 @@ macro_rules! basic_or {
 
 /// Macro to create a very simple AND-type filter for multiple values.
 macro_rules! basic_and {
-    ($arg:expr, $pred:path) => {
-        fn filter(&self, replay: &Replay, matches: &ArgMatches) -> bool {
-            if let Some(mut values) = matches.values_of($arg) {
-                values.all(|p| $pred(p, replay))
-            } else {
+    ($field:ident, $pred:path) => {
+        fn is_active(&self, query: &Query) -> bool {
+            !query.$field.is_empty()
+        }
+
+        fn filter(&self, replay: &Replay, query: &Query) -> bool {
+            if query.$field.is_empty() {
                 true
+            } else {
+                query.$field.iter().all(|p| $pred(p, replay))
             }
         }
     };
@@ -37,9 +47,13 @@ macro_rules! basic_and {
 
 /// Macro to create a very simple filter for single values.
 macro_rules! basic_presence {
-    ($arg:expr, $pred:path) => {
-        fn filter(&self, replay: &Replay, matches: &ArgMatches) -> bool {
-            if matches.is_present($arg) {
+    ($field:ident, $pred:path) => {
+        fn is_active(&self, query: &Query) -> bool {
+            query.$field
+        }
+
+        fn filter(&self, replay: &Replay, query: &Query) -> bool {
+            if query.$field {
                 $pred(replay)
             } else {
                 true
@@ -48,55 +62,108 @@ macro_rules! basic_presence {
     };
 }
 
+/// Macro to create a very simple filter for optional values.
+macro_rules! basic_option {
+    ($field:ident, $pred:path) => {
+        fn is_active(&self, query: &Query) -> bool {
+            query.$field.is_some()
+        }
+
+        fn filter(&self, replay: &Replay, query: &Query) -> bool {
+            if let Some(value) = &query.$field {
+                $pred(value, replay)
+            } else {
+                true
+            }
+        }
+    };
+}
+
+mod clock;
+mod clock_usage;
 mod completed_missions;
 mod completed_missions_all;
 mod countdown;
+mod days_of_week;
+mod expression;
+mod game_duration;
 mod game_modes;
+mod hour_range;
 mod maps;
+mod negated;
 mod pair;
 mod players;
+mod players_regex;
 mod results;
+mod since;
 mod sniper_win;
 mod snipers;
 mod spies;
 mod spy_win;
+mod until;
 
-use completed_missions::CompletedMissions;
-use completed_missions_all::CompletedMissionsAll;
-use countdown::Countdown;
-use game_modes::GameModes;
-use maps::Maps;
-use pair::Pair;
-use players::Players;
-use results::Results;
-use sniper_win::SniperWin;
-use snipers::Snipers;
-use spies::Spies;
-use spy_win::SpyWin;
+pub(crate) use clock::Clock;
+pub(crate) use clock_usage::ClockUsage;
+pub(crate) use completed_missions::CompletedMissions;
+pub(crate) use completed_missions_all::CompletedMissionsAll;
+pub(crate) use countdown::Countdown;
+pub(crate) use days_of_week::DaysOfWeek;
+use expression::Where;
+pub(crate) use game_duration::GameDuration;
+pub(crate) use game_modes::GameModes;
+pub(crate) use hour_range::HourRange;
+pub(crate) use maps::Maps;
+use negated::Negated;
+pub(crate) use pair::Pair;
+pub(crate) use players::Players;
+pub(crate) use players_regex::PlayersRegex;
+pub(crate) use results::Results;
+pub(crate) use since::Since;
+pub(crate) use sniper_win::SniperWin;
+pub(crate) use snipers::Snipers;
+pub(crate) use spies::Spies;
+pub(crate) use spy_win::SpyWin;
+pub(crate) use until::Until;
 
 macro_rules! register_filters {
     ($filters:ident, $($filter:ident),*) => {
-        let $filters: &[&dyn Filter] = &[$(&$filter {}),*];
+        const $filters: &[&dyn Filter] = &[$(&$filter {}),*];
     };
 }
 
-/// Filters the replays based on various command line arguments.
-pub fn filter(replay: &Replay, matches: &ArgMatches) -> Result<bool> {
-    register_filters!(
-        filters,
-        CompletedMissions,
-        CompletedMissionsAll,
-        Countdown,
-        GameModes,
-        Maps,
-        Pair,
-        Players,
-        Results,
-        SniperWin,
-        Snipers,
-        Spies,
-        SpyWin
-    );
-
-    Ok(filters.iter().all(|f| f.filter(replay, matches)))
+register_filters!(
+    FILTERS,
+    Clock,
+    ClockUsage,
+    CompletedMissions,
+    CompletedMissionsAll,
+    Countdown,
+    DaysOfWeek,
+    GameDuration,
+    GameModes,
+    HourRange,
+    Maps,
+    Pair,
+    Players,
+    PlayersRegex,
+    Results,
+    Since,
+    SniperWin,
+    Snipers,
+    Spies,
+    SpyWin,
+    Until,
+    Where
+);
+
+/// Filters the replays based on a query.
+pub fn filter(replay: &Replay, query: &Query) -> bool {
+    FILTERS
+        .iter()
+        .all(|f| f.filter(replay, query) && Negated { inner: *f }.filter(replay, query))
+}
+
+/// Checks if any of the filters are in use by a query.
+pub fn is_active(query: &Query) -> bool {
+    FILTERS.iter().any(|f| f.is_active(query))
 }
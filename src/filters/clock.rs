@@ -0,0 +1,19 @@
+use super::Filter;
+use crate::query::{Query, ValueRange};
+use spyparty::Replay;
+
+/// Filters replays that started with a specific clock.
+pub struct Clock {}
+
+impl Clock {
+    pub(crate) fn predicate(clock: &ValueRange<u32>, replay: &Replay) -> bool {
+        match replay.header.result_data.clock_start {
+            Some(clock_start) => clock.contains(clock_start),
+            None => false,
+        }
+    }
+}
+
+impl Filter for Clock {
+    basic_option!(clock, Self::predicate);
+}
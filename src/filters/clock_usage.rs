@@ -0,0 +1,20 @@
+use super::Filter;
+use crate::query::{Query, ValueRange};
+use crate::utils::clock_usage;
+use spyparty::Replay;
+
+/// Filters replays that used a specific fraction of the starting clock.
+pub struct ClockUsage {}
+
+impl ClockUsage {
+    pub(crate) fn predicate(usage: &ValueRange<f32>, replay: &Replay) -> bool {
+        match clock_usage(replay) {
+            Some(clock_usage) => usage.contains(clock_usage),
+            None => false,
+        }
+    }
+}
+
+impl Filter for ClockUsage {
+    basic_option!(clock_usage, Self::predicate);
+}
@@ -1,27 +1,20 @@
 use super::Filter;
-use clap::ArgMatches;
-use log::error;
-use spyparty::Replay;
-use std::convert::TryInto;
+use crate::query::Query;
+use spyparty::{Mission, Replay};
 
 /// Filters replays that contain specific missions, any of which were completed.
 pub struct CompletedMissions {}
 
 impl CompletedMissions {
-    fn predicate(arg: &str, replay: &Replay) -> bool {
-        if let Ok(mission) = arg.try_into() {
-            replay
-                .header
-                .result_data
-                .completed_missions
-                .contains(&mission)
-        } else {
-            error!("'{}' is not a valid option for the mission filter", arg);
-            false
-        }
+    pub(crate) fn predicate(mission: &Mission, replay: &Replay) -> bool {
+        replay
+            .header
+            .result_data
+            .completed_missions
+            .contains(mission)
     }
 }
 
 impl Filter for CompletedMissions {
-    basic_or!("completed-missions", Self::predicate);
+    basic_or!(completed_missions, Self::predicate);
 }
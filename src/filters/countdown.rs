@@ -1,12 +1,12 @@
 use super::Filter;
-use clap::ArgMatches;
+use crate::query::Query;
 use spyparty::{GameMode, Replay};
 
 /// Filters replays that contain a mission win countdown.
 pub struct Countdown {}
 
 impl Countdown {
-    fn predicate(replay: &Replay) -> bool {
+    pub(crate) fn predicate(replay: &Replay) -> bool {
         let missions_required = match replay.header.result_data.game_mode {
             GameMode::Any(required, _) => required,
             GameMode::Pick(required, _) => required,
@@ -18,5 +18,5 @@ impl Countdown {
 }
 
 impl Filter for Countdown {
-    basic_presence!("countdown", Self::predicate);
+    basic_presence!(countdown, Self::predicate);
 }
@@ -0,0 +1,18 @@
+use super::Filter;
+use crate::query::Query;
+use crate::utils::start_time;
+use chrono::{Datelike, Weekday};
+use spyparty::Replay;
+
+/// Filters replays that started on a specific day of the week.
+pub struct DaysOfWeek {}
+
+impl DaysOfWeek {
+    pub(crate) fn predicate(day: &Weekday, replay: &Replay) -> bool {
+        start_time(replay).weekday() == *day
+    }
+}
+
+impl Filter for DaysOfWeek {
+    basic_or!(days_of_week, Self::predicate);
+}
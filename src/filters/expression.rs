@@ -0,0 +1,17 @@
+use super::Filter;
+use crate::expression::Expression;
+use crate::query::Query;
+use spyparty::Replay;
+
+/// Filters replays that satisfy a boolean expression of conditions.
+pub struct Where {}
+
+impl Where {
+    fn predicate(expression: &Expression, replay: &Replay) -> bool {
+        expression.evaluate(replay)
+    }
+}
+
+impl Filter for Where {
+    basic_option!(expression, Self::predicate);
+}
@@ -0,0 +1,16 @@
+use super::Filter;
+use crate::query::{Query, ValueRange};
+use spyparty::Replay;
+
+/// Filters replays that lasted for a specific length of time.
+pub struct GameDuration {}
+
+impl GameDuration {
+    pub(crate) fn predicate(duration: &ValueRange<f32>, replay: &Replay) -> bool {
+        duration.contains(replay.header.duration)
+    }
+}
+
+impl Filter for GameDuration {
+    basic_option!(duration, Self::predicate);
+}
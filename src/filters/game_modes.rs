@@ -1,39 +1,23 @@
 use super::Filter;
-use clap::ArgMatches;
-use log::error;
+use crate::query::{ModeFilter, Query};
 use spyparty::{GameMode, Replay};
-use std::convert::TryInto;
 
 /// Filters replays that are specific game modes.
 pub struct GameModes {}
 
 impl GameModes {
-    fn predicate(arg: &str, replay: &Replay) -> bool {
-        match arg {
-            "any" | "a" => match replay.header.result_data.game_mode {
-                GameMode::Any(..) => true,
-                _ => false,
-            },
-            "pick" | "p" => match replay.header.result_data.game_mode {
-                GameMode::Pick(..) => true,
-                _ => false,
-            },
-            "known" | "k" => match replay.header.result_data.game_mode {
-                GameMode::Known(..) => true,
-                _ => false,
-            },
-            _ => {
-                if let Ok(mode) = arg.try_into() {
-                    replay.header.result_data.game_mode == mode
-                } else {
-                    error!("'{}' is not a valid option for the game mode filter", arg);
-                    false
-                }
-            }
+    pub(crate) fn predicate(mode: &ModeFilter, replay: &Replay) -> bool {
+        let game_mode = &replay.header.result_data.game_mode;
+
+        match mode {
+            ModeFilter::Any => matches!(game_mode, GameMode::Any(..)),
+            ModeFilter::Pick => matches!(game_mode, GameMode::Pick(..)),
+            ModeFilter::Known => matches!(game_mode, GameMode::Known(..)),
+            ModeFilter::Exact(mode) => game_mode == mode,
         }
     }
 }
 
 impl Filter for GameModes {
-    basic_or!("modes", Self::predicate);
+    basic_or!(modes, Self::predicate);
 }
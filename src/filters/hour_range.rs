@@ -0,0 +1,25 @@
+use super::Filter;
+use crate::query::Query;
+use crate::utils::start_time;
+use chrono::Timelike;
+use spyparty::Replay;
+
+/// Filters replays that started within a range of hours of the day.
+pub struct HourRange {}
+
+impl HourRange {
+    pub(crate) fn predicate(&(start, end): &(u32, u32), replay: &Replay) -> bool {
+        let hour = start_time(replay).hour();
+
+        // A range such as 22..2 wraps around midnight
+        if start <= end {
+            hour >= start && hour <= end
+        } else {
+            hour >= start || hour <= end
+        }
+    }
+}
+
+impl Filter for HourRange {
+    basic_option!(hour_range, Self::predicate);
+}
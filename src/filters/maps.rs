@@ -1,26 +1,20 @@
 use super::Filter;
-use clap::ArgMatches;
-use log::error;
+use crate::query::Query;
 use spyparty::Map;
 use spyparty::Replay;
-use std::convert::TryInto;
 
 /// Filters replays that take place on a specific map.
 pub struct Maps {}
 
 impl Maps {
-    fn predicate(arg: &str, replay: &Replay) -> bool {
-        if let Ok(map) = arg.try_into() {
-            replay.header.result_data.map == map
-        } else if arg.to_ascii_lowercase() == "unknown" {
-            matches!(replay.header.result_data.map, Map::Unknown(_))
-        } else {
-            error!("'{}' is not a valid option for the map filter", arg);
-            false
+    pub(crate) fn predicate(map: &Map, replay: &Replay) -> bool {
+        match map {
+            Map::Unknown(_) => matches!(replay.header.result_data.map, Map::Unknown(_)),
+            _ => &replay.header.result_data.map == map,
         }
     }
 }
 
 impl Filter for Maps {
-    basic_or!("maps", Self::predicate);
+    basic_or!(maps, Self::predicate);
 }
@@ -0,0 +1,26 @@
+use super::Filter;
+use crate::query::Query;
+use spyparty::Replay;
+
+/// Filters replays that are NOT matched by another filter, using the values from the excluded part
+/// of the query.
+pub struct Negated<'a> {
+    /// The filter to negate.
+    pub inner: &'a dyn Filter,
+}
+
+impl Filter for Negated<'_> {
+    fn is_active(&self, query: &Query) -> bool {
+        match &query.exclude {
+            Some(exclude) => self.inner.is_active(exclude),
+            None => false,
+        }
+    }
+
+    fn filter(&self, replay: &Replay, query: &Query) -> bool {
+        match &query.exclude {
+            Some(exclude) if self.inner.is_active(exclude) => !self.inner.filter(replay, exclude),
+            _ => true,
+        }
+    }
+}
@@ -1,16 +1,32 @@
 use super::Filter;
+use crate::query::Query;
 use crate::utils::has_player;
-use clap::ArgMatches;
 use spyparty::Replay;
 
 /// Filters replays that contain a pair of players.
 pub struct Pair {}
 
 impl Pair {
-    fn predicate(arg: &str, replay: &Replay) -> bool {
+    pub(crate) fn predicate(arg: &str, replay: &Replay) -> bool {
         has_player(replay, arg)
     }
 }
+
 impl Filter for Pair {
-    basic_and!("pair", Self::predicate);
+    fn is_active(&self, query: &Query) -> bool {
+        !query.pair.is_empty()
+    }
+
+    // A glob pattern in the pair also matches the identities of the user names it matches, which
+    // can't be added to the pair like they are for the other player filters
+    fn filter(&self, replay: &Replay, query: &Query) -> bool {
+        query.pair.iter().all(|player| {
+            Self::predicate(player, replay)
+                || query
+                    .aliases
+                    .matching_identities(player)
+                    .into_iter()
+                    .any(|identity| Self::predicate(identity, replay))
+        })
+    }
 }
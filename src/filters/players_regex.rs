@@ -0,0 +1,17 @@
+use super::Filter;
+use crate::query::Query;
+use crate::utils::{sniper_matches, spy_matches};
+use regex::Regex;
+use spyparty::Replay;
+
+/// Filters replays where the name of the spy or the sniper matches a regular expression.
+pub struct PlayersRegex {}
+
+impl PlayersRegex {
+    pub(crate) fn predicate(regex: &Regex, replay: &Replay) -> bool {
+        spy_matches(replay, regex) || sniper_matches(replay, regex)
+    }
+}
+impl Filter for PlayersRegex {
+    basic_or!(players_regex, Self::predicate);
+}
@@ -1,21 +1,16 @@
 use super::Filter;
-use clap::ArgMatches;
-use spyparty::Replay;
-use std::convert::TryInto;
+use crate::query::Query;
+use spyparty::{GameResult, Replay};
 
 /// Filters replays that end in a specific result.
 pub struct Results {}
 
 impl Results {
-    fn predicate(arg: &str, replay: &Replay) -> bool {
-        if let Ok(result) = arg.try_into() {
-            replay.header.result_data.game_result == result
-        } else {
-            false
-        }
+    pub(crate) fn predicate(result: &GameResult, replay: &Replay) -> bool {
+        &replay.header.result_data.game_result == result
     }
 }
 
 impl Filter for Results {
-    basic_or!("results", Self::predicate);
+    basic_or!(results, Self::predicate);
 }
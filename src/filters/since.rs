@@ -0,0 +1,18 @@
+use super::Filter;
+use crate::query::Query;
+use crate::utils::start_time;
+use chrono::{DateTime, Local};
+use spyparty::Replay;
+
+/// Filters replays that started at or after a specific time.
+pub struct Since {}
+
+impl Since {
+    pub(crate) fn predicate(since: &DateTime<Local>, replay: &Replay) -> bool {
+        start_time(replay) >= *since
+    }
+}
+
+impl Filter for Since {
+    basic_option!(since, Self::predicate);
+}
@@ -1,16 +1,16 @@
 use super::Filter;
-use clap::ArgMatches;
+use crate::query::Query;
 use spyparty::Replay;
 
 /// Filters replays that end in a sniper win.
 pub struct SniperWin {}
 
 impl SniperWin {
-    fn predicate(replay: &Replay) -> bool {
+    pub(crate) fn predicate(replay: &Replay) -> bool {
         replay.is_sniper_win()
     }
 }
 
 impl Filter for SniperWin {
-    basic_presence!("sniperwin", Self::predicate);
+    basic_presence!(sniper_win, Self::predicate);
 }
@@ -1,16 +1,16 @@
 use super::Filter;
-use clap::ArgMatches;
+use crate::query::Query;
 use spyparty::Replay;
 
 /// Filters replays that end in a spy win.
 pub struct SpyWin {}
 
 impl SpyWin {
-    fn predicate(replay: &Replay) -> bool {
+    pub(crate) fn predicate(replay: &Replay) -> bool {
         replay.is_spy_win()
     }
 }
 
 impl Filter for SpyWin {
-    basic_presence!("spywin", Self::predicate);
+    basic_presence!(spy_win, Self::predicate);
 }
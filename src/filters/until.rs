@@ -0,0 +1,18 @@
+use super::Filter;
+use crate::query::Query;
+use crate::utils::start_time;
+use chrono::{DateTime, Local};
+use spyparty::Replay;
+
+/// Filters replays that started on or before a specific time.
+pub struct Until {}
+
+impl Until {
+    pub(crate) fn predicate(until: &DateTime<Local>, replay: &Replay) -> bool {
+        start_time(replay) <= *until
+    }
+}
+
+impl Filter for Until {
+    basic_option!(until, Self::predicate);
+}
@@ -0,0 +1,124 @@
+use crate::utils::{has_sniper, has_spy, percentage};
+use crate::MatchedReplay;
+use std::collections::VecDeque;
+
+/// The number of recent games the rolling win rate is calculated over by default.
+pub const DEFAULT_FORM_GAMES: usize = 20;
+
+/// The results of a player in a particular role, in the order the games were played.
+#[derive(Clone, Debug, Default)]
+pub struct RoleForm {
+    pub wins: u32,
+    pub losses: u32,
+    pub longest_win_streak: u32,
+    pub longest_loss_streak: u32,
+    /// The streak the player is currently on, which is positive for wins and negative for losses.
+    pub current_streak: i32,
+    /// The results of the most recent games, oldest first, where `true` is a win.
+    recent: VecDeque<bool>,
+}
+
+/// The form of a single player, as the spy, as the sniper and overall.
+#[derive(Clone, Debug)]
+pub struct PlayerForm {
+    pub user_name: String,
+    pub display_name: String,
+    pub spy: RoleForm,
+    pub sniper: RoleForm,
+    pub overall: RoleForm,
+}
+
+impl RoleForm {
+    /// Adds the result of a game, keeping only the last `games` results for the rolling win rate.
+    fn add(&mut self, won: bool, games: usize) {
+        if won {
+            self.wins += 1;
+            self.current_streak = self.current_streak.max(0) + 1;
+            self.longest_win_streak = self.longest_win_streak.max(self.current_streak as u32);
+        } else {
+            self.losses += 1;
+            self.current_streak = self.current_streak.min(0) - 1;
+            self.longest_loss_streak = self
+                .longest_loss_streak
+                .max(self.current_streak.unsigned_abs());
+        }
+
+        self.recent.push_back(won);
+        if self.recent.len() > games {
+            self.recent.pop_front();
+        }
+    }
+
+    /// The number of finished games played.
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses
+    }
+
+    /// The number of games the rolling win rate is based on.
+    pub fn recent_games(&self) -> usize {
+        self.recent.len()
+    }
+
+    /// The percentage of the most recent games which were won, if any games were played.
+    pub fn recent_win_percentage(&self) -> Option<f32> {
+        if self.recent.is_empty() {
+            return None;
+        }
+
+        let wins = self.recent.iter().filter(|&&won| won).count();
+        Some(percentage(wins as u32, self.recent.len() as u32))
+    }
+}
+
+/// Works out the form of each of a set of players by going through the replays in order, with a
+/// rolling win rate over their last `games` games.
+///
+/// The replays should already be sorted by start time. Unfinished games are ignored, as are
+/// players who didn't play any of the games.
+pub fn calculate(replays: &[MatchedReplay], players: &[&str], games: usize) -> Vec<PlayerForm> {
+    players
+        .iter()
+        .filter_map(|player| calculate_player(replays, player, games))
+        .collect()
+}
+
+/// Works out the form of a single player, if they played any of the games.
+fn calculate_player(replays: &[MatchedReplay], player: &str, games: usize) -> Option<PlayerForm> {
+    let mut form: Option<PlayerForm> = None;
+
+    for replay in replays {
+        let replay = &replay.inner;
+        let (is_spy, user_name, display_name) = if has_spy(replay, player) {
+            (true, &replay.header.spy_user_name, replay.spy_name())
+        } else if has_sniper(replay, player) {
+            (false, &replay.header.sniper_user_name, replay.sniper_name())
+        } else {
+            continue;
+        };
+
+        if !replay.is_spy_win() && !replay.is_sniper_win() {
+            continue;
+        }
+
+        let form = form.get_or_insert_with(|| PlayerForm {
+            user_name: user_name.clone(),
+            display_name: display_name.clone(),
+            spy: Default::default(),
+            sniper: Default::default(),
+            overall: Default::default(),
+        });
+        let won = replay.is_spy_win() == is_spy;
+
+        // Keep the most recent display name
+        form.display_name = display_name.clone();
+
+        if is_spy {
+            form.spy.add(won, games);
+        } else {
+            form.sniper.add(won, games);
+        }
+        form.overall.add(won, games);
+    }
+
+    form
+}
@@ -0,0 +1,243 @@
+use crate::errors::*;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use spyparty::Replay;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// The version of the index file format, which should be bumped whenever `IndexFile` changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// The version of the spyparty parser the cached headers were parsed with, which must match the
+/// version of the spyparty dependency pinned in Cargo.toml.
+pub const PARSER_VERSION: &str = "0.1.9";
+
+/// An on-disk cache of replay headers, so that replays which haven't changed since they were last
+/// parsed don't have to be read from disk again.
+///
+/// Each replay is keyed by its canonical path, and is only used if the size and modification time
+/// of the file still match. The whole index is thrown away if it was created by a different version
+/// of the spyparty parser.
+pub struct Index {
+    path: PathBuf,
+    file: IndexFile,
+    changed: bool,
+}
+
+/// The contents of an index file.
+#[derive(Serialize, Deserialize)]
+struct IndexFile {
+    format_version: u32,
+    parser_version: String,
+    entries: HashMap<PathBuf, IndexEntry>,
+}
+
+/// A cached replay header.
+#[derive(Serialize, Deserialize)]
+pub struct IndexEntry {
+    size: u64,
+    modified: (u64, u32),
+    header: Vec<u8>,
+}
+
+impl Index {
+    /// Gets the default location of the index, inside the user's cache directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|mut path| {
+            path.push("spyparsey");
+            path.push("index");
+            path
+        })
+    }
+
+    /// Loads an index from a path, starting with an empty index if it doesn't exist, can't be
+    /// read or is out of date.
+    pub fn load(path: &Path) -> Index {
+        let file = match File::open(path) {
+            Ok(file) => match bincode::deserialize_from::<_, IndexFile>(BufReader::new(file)) {
+                Ok(file) if file.is_current() => Some(file),
+                Ok(_) => {
+                    warn!("rebuilding out of date index '{}'", path.display());
+                    None
+                }
+                Err(e) => {
+                    warn!("failed to read index '{}' ({})", path.display(), e);
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
+        match file {
+            Some(file) => Index {
+                path: path.to_path_buf(),
+                file,
+                changed: false,
+            },
+            None => Index::empty(path),
+        }
+    }
+
+    /// Creates an empty index, which will replace any existing index at the path when saved.
+    pub fn empty(path: &Path) -> Index {
+        Index {
+            path: path.to_path_buf(),
+            file: IndexFile::new(),
+            changed: true,
+        }
+    }
+
+    /// Deletes the index at a path, if there is one.
+    pub fn remove(path: &Path) -> Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                Err(e).chain_err(|| format!("failed to delete index '{}'", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The number of replays in the index.
+    pub fn len(&self) -> usize {
+        self.file.entries.len()
+    }
+
+    /// Checks if the index contains no replays.
+    pub fn is_empty(&self) -> bool {
+        self.file.entries.is_empty()
+    }
+
+    /// Tries to parse a replay at a specific path, using the cached header if the file hasn't
+    /// changed. If the replay had to be read from disk, a new entry for the index is also returned.
+    pub fn parse(&self, path: &Path) -> (Option<Replay>, Option<IndexEntry>) {
+        let (size, modified) = match file_stamp(path) {
+            Ok(stamp) => stamp,
+            Err(_) => return (crate::parse(path), None),
+        };
+
+        if let Some(entry) = self.file.entries.get(path) {
+            if entry.size == size && entry.modified == modified {
+                if let Ok(replay) = Replay::from_reader(&mut entry.header.as_slice()) {
+                    return (Some(replay), None);
+                }
+            }
+        }
+
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(_) => {
+                warn!("failed to read file '{}'", path.display());
+                return (None, None);
+            }
+        };
+        let mut reader = RecordingReader {
+            inner: BufReader::new(file),
+            recorded: Vec::new(),
+        };
+
+        match crate::parse_reader(&mut reader, path) {
+            Some(replay) => (
+                Some(replay),
+                Some(IndexEntry {
+                    size,
+                    modified,
+                    header: reader.recorded,
+                }),
+            ),
+            None => (None, None),
+        }
+    }
+
+    /// Adds (or replaces) the cached header of a replay.
+    pub fn insert(&mut self, path: PathBuf, entry: IndexEntry) {
+        self.file.entries.insert(path, entry);
+        self.changed = true;
+    }
+
+    /// Removes the entries of replays under any of the root paths which no longer exist, such as
+    /// those that have been deleted or moved somewhere else. Entries outside of the roots are kept,
+    /// since they may still exist but just weren't searched this time.
+    pub fn prune<P: AsRef<Path>>(&mut self, roots: &[P]) {
+        let roots = roots
+            .iter()
+            .filter_map(|root| fs::canonicalize(root).ok())
+            .collect::<Vec<_>>();
+        let len = self.file.entries.len();
+
+        self.file
+            .entries
+            .retain(|path, _| path.is_file() || !roots.iter().any(|root| path.starts_with(root)));
+
+        if self.file.entries.len() != len {
+            self.changed = true;
+        }
+    }
+
+    /// Writes the index to disk, if anything has changed since it was loaded.
+    pub fn save(&self) -> Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).chain_err(|| "failed to create index directory")?;
+        }
+
+        // Write to a temporary file first, so other runs never see a half-written index
+        let temp_path = self.path.with_extension("tmp");
+        let file = File::create(&temp_path).chain_err(|| "failed to create index")?;
+        bincode::serialize_into(BufWriter::new(file), &self.file)
+            .chain_err(|| "failed to write index")?;
+        fs::rename(&temp_path, &self.path).chain_err(|| "failed to replace index")?;
+
+        Ok(())
+    }
+}
+
+impl IndexFile {
+    /// Creates an empty index file for the current version of the parser.
+    fn new() -> Self {
+        IndexFile {
+            format_version: FORMAT_VERSION,
+            parser_version: PARSER_VERSION.to_string(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Checks if the index file was created by the current version of the parser.
+    fn is_current(&self) -> bool {
+        self.format_version == FORMAT_VERSION && self.parser_version == PARSER_VERSION
+    }
+}
+
+/// Gets the size and modification time of a file, used to tell if it has changed.
+fn file_stamp(path: &Path) -> io::Result<(u64, (u64, u32))> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    Ok((
+        metadata.len(),
+        (modified.as_secs(), modified.subsec_nanos()),
+    ))
+}
+
+/// A reader which keeps a copy of every byte read through it, used to capture exactly the bytes
+/// the parser needed for a replay's header.
+struct RecordingReader<R> {
+    inner: R,
+    recorded: Vec<u8>,
+}
+
+impl<R: Read> Read for RecordingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.recorded.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
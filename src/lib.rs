@@ -0,0 +1,206 @@
+//! A library for parsing and querying a collection of SpyParty replays.
+//!
+//! Replays are matched using a [`Query`](query/struct.Query.html), which can then be shown in
+//! various forms using the [`output`](output/index.html) module:
+//!
+//! ```no_run
+//! use spyparsey::Query;
+//! use spyparty::Map;
+//!
+//! let query = Query::new().players(vec!["plastikqs"]).maps(vec![Map::Balcony]);
+//! let collection = spyparsey::search(vec!["replays"], &query).unwrap();
+//!
+//! println!("{}", collection.replays.len());
+//! ```
+
+#[macro_use]
+extern crate error_chain;
+
+pub mod aliases;
+pub mod expression;
+pub mod filters;
+pub mod form;
+pub mod index;
+pub mod matched_replay;
+pub mod matches;
+pub mod organize;
+pub mod output;
+pub mod query;
+pub mod ratings;
+pub mod utils;
+
+pub mod errors {
+    // Create the Error, ErrorKind, ResultExt, and Result types
+    error_chain! {}
+}
+
+pub use crate::aliases::Aliases;
+pub use crate::expression::Expression;
+pub use crate::index::Index;
+pub use crate::matched_replay::{MatchedReplay, MatchedReplayCollection};
+pub use crate::matches::{Match, MatchResult};
+pub use crate::query::{ModeFilter, Query};
+pub use crate::ratings::{RatingSystem, Ratings};
+
+use crate::errors::*;
+use log::warn;
+use rayon::prelude::*;
+use spyparty::{Map, Replay};
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicIsize, Ordering};
+use walkdir::WalkDir;
+
+/// Finds, parses and filters all the replays in a set of paths, returning the matched replays
+/// with duplicates removed and sorted by start time.
+pub fn search<I, P>(paths: I, query: &Query) -> Result<MatchedReplayCollection>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let replay_paths = find_replays(paths)?;
+    let mut replay_collection = parse_and_filter_replays(replay_paths, query, None)?;
+
+    replay_collection.dedup_and_sort();
+    replay_collection.filter_matches(query)?;
+
+    Ok(replay_collection)
+}
+
+/// Steps recursively through a set of paths, collecting the paths of any replays.
+pub fn find_replays<I, P>(paths: I) -> Result<Vec<PathBuf>>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut replay_paths = vec![];
+
+    for path in paths {
+        for entry in WalkDir::new(path) {
+            // Ignore failed file reads
+            if let Ok(entry) = entry {
+                if let Some(ext) = entry.path().extension() {
+                    if ext == "replay" {
+                        replay_paths.push(entry.into_path());
+                    }
+                }
+            } else {
+                match entry.err().unwrap().path() {
+                    Some(path) => warn!("failed to read file '{}'", path.display()),
+                    None => warn!("failed to read file"),
+                }
+            }
+        }
+    }
+
+    Ok(replay_paths)
+}
+
+/// Parses the replays at each path and keeps only those matched by the query.
+///
+/// If an index is given, any replays which haven't changed since they were added to it are parsed
+/// from their cached headers, and any other replays are added to it by their canonical path.
+pub fn parse_and_filter_replays(
+    paths: Vec<PathBuf>,
+    query: &Query,
+    mut index: Option<&mut Index>,
+) -> Result<MatchedReplayCollection> {
+    let parsed = AtomicIsize::new(0);
+    let total = AtomicIsize::new(0);
+    let cached = index.as_deref();
+
+    let results = paths
+        .into_par_iter()
+        .filter_map(|path| {
+            let mut matched_replay = None;
+
+            // We have a possible replay, let's parse it!
+            let (replay, entry) = match cached {
+                Some(index) => {
+                    // The index is keyed on canonical paths, so that the same replay found through
+                    // different paths only has one entry
+                    let key = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                    let (replay, entry) = index.parse(&key);
+                    (replay, entry.map(|entry| (key, entry)))
+                }
+                None => (parse(&path), None),
+            };
+
+            if let Some(mut replay) = replay {
+                parsed.fetch_add(1, Ordering::SeqCst);
+                query.aliases.apply(&mut replay);
+
+                if query.prefilter(&replay) {
+                    matched_replay = Some(MatchedReplay {
+                        inner: replay,
+                        path: path.display().to_string(),
+                    });
+                }
+            }
+
+            total.fetch_add(1, Ordering::SeqCst);
+
+            if matched_replay.is_some() || entry.is_some() {
+                Some((matched_replay, entry))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut replays = Vec::with_capacity(results.len());
+    let mut read = 0;
+
+    for (matched_replay, entry) in results {
+        if let Some(matched_replay) = matched_replay {
+            replays.push(matched_replay);
+        }
+
+        if let Some((path, entry)) = entry {
+            read += 1;
+
+            if let Some(index) = index.as_mut() {
+                index.insert(path, entry);
+            }
+        }
+    }
+
+    let parsed = parsed.into_inner();
+
+    Ok(MatchedReplayCollection {
+        replays,
+        total: total.into_inner(),
+        parsed,
+        cached: if index.is_some() { parsed - read } else { 0 },
+    })
+}
+
+/// Tries to parse a replay at a specific path.
+pub fn parse(path: &Path) -> Option<Replay> {
+    // Ignore failed file reads
+    if let Ok(file) = File::open(path) {
+        let mut reader = BufReader::new(file);
+        parse_reader(&mut reader, path)
+    } else {
+        warn!("failed to read file '{}'", path.display());
+        None
+    }
+}
+
+/// Tries to parse a replay from a reader, using the path it was read from for any warnings.
+fn parse_reader<R: Read>(reader: &mut R, path: &Path) -> Option<Replay> {
+    // Ignore failed parses
+    match Replay::from_reader(reader) {
+        Ok(replay) => {
+            if let Map::Unknown(x) = replay.header.result_data.map {
+                warn!("unrecognised map in '{}' (0x{:x})", path.display(), x);
+            }
+            Some(replay)
+        }
+        Err(e) => {
+            warn!("failed to parse replay '{}' ({})", path.display(), e);
+            None
+        }
+    }
+}
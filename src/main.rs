@@ -1,30 +1,22 @@
 #[macro_use]
 extern crate error_chain;
 
-mod filters;
-mod matched_replay;
-mod output;
-mod utils;
+mod cli;
+mod config;
+mod default_path;
 
-use crate::matched_replay::{MatchedReplay, MatchedReplayCollection};
 use clap::load_yaml;
 use clap::{App, ArgMatches};
+use config::Config;
 use log::{info, warn};
-use rayon::prelude::*;
-use spyparty::{Map, Replay};
-use std::fs::File;
-use std::io::BufReader;
+use spyparsey::errors::*;
+use spyparsey::organize::{self, Action};
+use spyparsey::output;
+use spyparsey::{Index, MatchedReplay, Query};
+use std::ffi::OsString;
+use std::iter;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicIsize, Ordering};
 use std::time::{Duration, SystemTime};
-use walkdir::WalkDir;
-
-mod errors {
-    // Create the Error, ErrorKind, ResultExt, and Result types
-    error_chain! {}
-}
-
-use crate::errors::*;
 
 fn main() {
     if let Err(e) = run() {
@@ -34,13 +26,13 @@ fn main() {
 
         writeln!(stderr, "{}", e).expect(errmsg);
 
+        for e in e.iter().skip(1) {
+            writeln!(stderr, "caused by: {}", e).expect(errmsg);
+        }
+
         // The backtrace is not always generated. Try to run this example
         // with `RUST_BACKTRACE=1`.
         if let Some(backtrace) = e.backtrace() {
-            for e in e.iter().skip(1) {
-                writeln!(stderr, "caused by: {}", e).expect(errmsg);
-            }
-
             writeln!(stderr, "backtrace: {:?}", backtrace).expect(errmsg);
         }
 
@@ -50,7 +42,11 @@ fn main() {
 
 fn run() -> Result<()> {
     let yaml = load_yaml!("cli.yml");
-    let matches = App::from_yaml(yaml).get_matches();
+    let filters = load_yaml!("filters.yml");
+    let negated = cli::negated_filters(filters);
+    let subcommands = cli::subcommand_names(yaml);
+    let app = || cli::add_filters(App::from_yaml(yaml), filters, &negated);
+    let mut matches = app().get_matches_from(args(&[], &[], &subcommands));
 
     // Start logging
     let verbose = matches.occurrences_of("verbosity") as usize;
@@ -60,57 +56,144 @@ fn run() -> Result<()> {
         .init()
         .chain_err(|| "Failed to start logging.")?;
 
-    if matches.is_present("paths") {
-        process_replays(matches.values_of("paths").unwrap(), &matches)
-    } else {
-        let default_path = get_default_path().chain_err(|| "Could not locate the default SpyParty replays directory. Consider using --path to specify a directory instead.")?;
-        process_replays(vec![default_path], &matches)
+    // Add the default command and the arguments of a saved query, and then the defaults from the
+    // config for any arguments that still haven't been passed, parsing everything again each time.
+    // Extra arguments go after the ones passed, so they're parsed as part of the subcommand.
+    let config = Config::load(&matches)?;
+    let command = config.command(&matches);
+    let mut extra_args = config.query_args(&matches)?;
+    matches = reparse(app(), &command, &extra_args, &subcommands)?;
+    let default_args = config.default_args(&matches);
+    if !default_args.is_empty() {
+        extra_args.extend(default_args);
+        matches = reparse(app(), &command, &extra_args, &subcommands)?;
     }
-}
 
-#[cfg(windows)]
-fn get_default_path() -> Result<PathBuf> {
-    if let Some(app_data) = std::env::var_os("LOCALAPPDATA") {
-        let mut path = PathBuf::from(app_data);
-        path.push("SpyParty");
-        path.push("replays");
-
-        if path.is_dir() {
-            Ok(path)
-        } else {
-            bail!("cannot find SpyParty replays directory")
+    // Clearing the index doesn't need any replays
+    if let Some(index_matches) = matches.subcommand_matches("index") {
+        if index_matches.is_present("clear") {
+            return clear_index();
         }
-    } else {
-        bail!("could not find local application data");
     }
+
+    let query = cli::query(&matches, &config)?;
+    let mut index = cli::index(&matches);
+    let paths = match matches.values_of("paths") {
+        Some(paths) => paths.map(PathBuf::from).collect(),
+        None => vec![default_path::find().chain_err(|| "Could not locate the default SpyParty replays directory. Consider using --path, setting paths in the config file or setting SPYPARSEY_REPLAYS to specify a directory instead.")?],
+    };
+
+    match matches.subcommand() {
+        ("index", _) => update_index(paths, &query, index),
+        ("organize", Some(organize_matches)) => {
+            let (dimensions, action) = cli::organize(organize_matches)?;
+            let destination = Path::new(organize_matches.value_of("destination").unwrap());
+            let replays = load_replays(paths, &query, index.as_mut())?;
+            let organized = organize::organize(&replays, destination, &dimensions, action)?;
+
+            match action {
+                Action::Copy => println!("Copied {} replays", organized),
+                Action::Move => println!("Moved {} replays", organized),
+                Action::DryRun => {}
+            }
+
+            Ok(())
+        }
+        _ => {
+            let output_mode = cli::output_mode(&matches)?;
+            let replays = load_replays(paths, &query, index.as_mut())?;
+
+            output::show(&replays, &query, output_mode)
+        }
+    }
+}
+
+/// Parses the command line arguments again, with a command inserted before them and extra
+/// arguments after them.
+fn reparse<'a>(
+    app: App<'a, '_>,
+    command: &[String],
+    extra: &[String],
+    subcommands: &[&str],
+) -> Result<ArgMatches<'a>> {
+    app.get_matches_from_safe(args(command, extra, subcommands))
+        .chain_err(|| "invalid arguments from the config file")
 }
 
-#[cfg(not(windows))]
-fn get_default_path() -> Result<PathBuf> {
-    bail!("default directory searching only available on Windows");
+/// Gets the command line arguments with a command inserted before them and extra arguments after
+/// them, moving any subcommand to the front (see `cli::subcommand_first`).
+fn args(command: &[String], extra: &[String], subcommands: &[&str]) -> Vec<OsString> {
+    let mut args = std::env::args_os();
+    let program = args.next().unwrap_or_default();
+    let args = command
+        .iter()
+        .map(OsString::from)
+        .chain(args)
+        .chain(extra.iter().map(OsString::from))
+        .collect();
+
+    iter::once(program)
+        .chain(cli::subcommand_first(args, subcommands))
+        .collect()
+}
+
+/// Deletes the index.
+fn clear_index() -> Result<()> {
+    match Index::default_path() {
+        Some(path) => Index::remove(&path)?,
+        None => bail!("could not locate a cache directory for the index"),
+    }
+
+    println!("Deleted the index");
+    Ok(())
+}
+
+/// Updates the index with every replay in the paths.
+fn update_index(paths: Vec<PathBuf>, query: &Query, index: Option<Index>) -> Result<()> {
+    let mut index = match index {
+        Some(index) => index,
+        None if Index::default_path().is_none() => {
+            bail!("could not locate a cache directory for the index")
+        }
+        None => bail!("the index can't be updated with --no-cache"),
+    };
+
+    load_replays(paths, query, Some(&mut index))?;
+    println!("{} replays in the index", index.len());
+
+    Ok(())
 }
 
-/// Steps recursively through a path and tries to parse and filter replays.
-fn process_replays<I, P>(paths: I, matches: &ArgMatches) -> Result<()>
-where
-    I: IntoIterator<Item = P>,
-    P: AsRef<Path>,
-{
+/// Steps recursively through a set of paths and tries to parse and filter replays, saving any
+/// newly parsed replays to the index.
+fn load_replays(
+    paths: Vec<PathBuf>,
+    query: &Query,
+    mut index: Option<&mut Index>,
+) -> Result<Vec<MatchedReplay>> {
     let mut now = SystemTime::now();
 
-    let replay_paths = find_replays(paths)?;
+    let replay_paths = spyparsey::find_replays(&paths)?;
 
     let find_time = now.elapsed().unwrap_or_else(|_| Duration::new(0, 0));
 
     now = SystemTime::now();
 
-    let mut replay_collection = parse_and_filter_replays(replay_paths, matches)?;
+    let mut replay_collection =
+        spyparsey::parse_and_filter_replays(replay_paths, query, index.as_deref_mut())?;
 
     replay_collection.dedup_and_sort();
+    replay_collection.filter_matches(query)?;
 
     let parse_time = now.elapsed().unwrap_or_else(|_| Duration::new(0, 0));
 
-    output::show(&replay_collection.replays, matches)?;
+    if let Some(index) = index.as_mut() {
+        index.prune(&paths);
+
+        if let Err(e) = index.save() {
+            warn!("failed to save index ({})", e);
+        }
+    }
 
     info!(
         "Found {} replays ({}.{}s)",
@@ -124,96 +207,10 @@ where
         parse_time.as_secs(),
         parse_time.subsec_millis()
     );
-    info!("Matched {} replays", replay_collection.replays.len());
-
-    Ok(())
-}
-
-fn find_replays<I, P>(paths: I) -> Result<Vec<PathBuf>>
-where
-    I: IntoIterator<Item = P>,
-    P: AsRef<Path>,
-{
-    let mut replay_paths = vec![];
-
-    for path in paths {
-        for entry in WalkDir::new(path) {
-            // Ignore failed file reads
-            if let Ok(entry) = entry {
-                if let Some(ext) = entry.path().extension() {
-                    if ext == "replay" {
-                        replay_paths.push(entry.into_path());
-                    }
-                }
-            } else {
-                match entry.err().unwrap().path() {
-                    Some(path) => warn!("failed to read file '{}'", path.display()),
-                    None => warn!("failed to read file"),
-                }
-            }
-        }
-    }
-
-    Ok(replay_paths)
-}
-
-fn parse_and_filter_replays(
-    paths: Vec<PathBuf>,
-    matches: &ArgMatches,
-) -> Result<MatchedReplayCollection> {
-    let parsed = AtomicIsize::new(0);
-    let total = AtomicIsize::new(0);
-
-    let replays = paths
-        .par_iter()
-        .filter_map(|path| {
-            let mut matched_replay = None;
-
-            // We have a possible replay, let's parse it!
-            if let Some(replay) = parse(path) {
-                parsed.fetch_add(1, Ordering::SeqCst);
-
-                if filters::filter(&replay, matches).unwrap_or(false) {
-                    matched_replay = Some(MatchedReplay {
-                        inner: replay,
-                        path: path.display().to_string(),
-                    });
-                }
-            }
-
-            total.fetch_add(1, Ordering::SeqCst);
-
-            matched_replay
-        })
-        .collect::<Vec<_>>();
-
-    Ok(MatchedReplayCollection {
-        replays,
-        total: total.into_inner(),
-        parsed: parsed.into_inner(),
-    })
-}
-
-/// Tries to parse a replay at a specific path.
-fn parse(path: &Path) -> Option<Replay> {
-    // Ignore failed file reads
-    if let Ok(file) = File::open(path) {
-        let mut reader = BufReader::new(file);
-        // Ignore failed parses
-        match Replay::from_reader(&mut reader) {
-            Ok(replay) => {
-                if let Map::Unknown(x) = replay.header.result_data.map {
-                    warn!("unrecognised map in '{}' (0x{:x})", path.display(), x);
-                }
-                return Some(replay);
-            }
-            Err(e) => {
-                warn!("failed to parse replay '{}' ({})", path.display(), e);
-            }
-        }
-    } else {
-        warn!("failed to read file '{}'", path.display());
+    if index.is_some() {
+        info!("Read {} replays from the index", replay_collection.cached);
     }
+    info!("Matched {} replays", replay_collection.replays.len());
 
-    None
+    Ok(replay_collection.replays)
 }
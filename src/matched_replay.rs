@@ -1,3 +1,6 @@
+use crate::errors::*;
+use crate::matches;
+use crate::query::Query;
 use spyparty::Replay;
 use std::cmp::Ordering;
 
@@ -40,6 +43,8 @@ pub struct MatchedReplayCollection {
     pub total: isize,
     /// The total number of replays parsed
     pub parsed: isize,
+    /// The number of parsed replays which were read from the index rather than from disk
+    pub cached: isize,
 }
 
 impl MatchedReplayCollection {
@@ -55,4 +60,24 @@ impl MatchedReplayCollection {
         self.replays.dedup();
         self.replays.sort_unstable();
     }
+
+    /// Applies any match-level filters in a query, which need the whole collection of replays
+    /// sorted by start time.
+    ///
+    /// Matches are grouped from every game that was kept by `Query::prefilter`, and then the rest
+    /// of the query is applied to the games of the matches which are kept, so the other filters
+    /// never change the result of a match.
+    pub fn filter_matches(&mut self, query: &Query) -> Result<()> {
+        if !query.groups_matches() {
+            return Ok(());
+        }
+
+        if query.match_result.is_some() && query.match_player().is_none() {
+            bail!("filtering on match results requires a player or pair");
+        }
+
+        matches::retain(&mut self.replays, query);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,187 @@
+use crate::query::Query;
+use crate::utils::{has_sniper, has_spy};
+use crate::MatchedReplay;
+use spyparty::Replay;
+use std::collections::HashMap;
+
+/// The longest gap in seconds between the end of one game and the start of the next for them to
+/// be part of the same match, so that separate sessions against the same opponent aren't merged.
+const MAX_GAP: f32 = 30.0 * 60.0;
+
+/// A match (or set) of consecutive games between the same pair of players.
+///
+/// SpyParty numbers each game in a match with an increasing play ID, so a match ends when the
+/// same pair play a game whose play ID doesn't follow on from their last one, or which starts long
+/// after their last one ended.
+pub struct Match<'a> {
+    /// The games in the match, in the order they were played.
+    pub games: Vec<&'a MatchedReplay>,
+    /// The user names of both players, with the spy of the first game first.
+    pub players: [String; 2],
+    /// The latest display names of both players.
+    pub display_names: [String; 2],
+    /// The number of games won by each player.
+    pub wins: [u32; 2],
+}
+
+/// The result of a match for one of its players.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchResult {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl<'a> Match<'a> {
+    /// Creates a match from its games, which must all be between the same pair of players.
+    fn new(games: Vec<&'a MatchedReplay>) -> Self {
+        let first = &games[0].inner.header;
+        let players = [first.spy_user_name.clone(), first.sniper_user_name.clone()];
+        let mut display_names = [String::new(), String::new()];
+        let mut wins = [0, 0];
+
+        for game in &games {
+            let replay = &game.inner;
+            let spy = if replay.header.spy_user_name == players[0] {
+                0
+            } else {
+                1
+            };
+
+            display_names[spy] = replay.spy_name().clone();
+            display_names[1 - spy] = replay.sniper_name().clone();
+
+            if replay.is_spy_win() {
+                wins[spy] += 1;
+            } else if replay.is_sniper_win() {
+                wins[1 - spy] += 1;
+            }
+        }
+
+        Match {
+            games,
+            players,
+            display_names,
+            wins,
+        }
+    }
+
+    /// Gets which of the two players a name refers to, if either.
+    pub fn position(&self, player: &str) -> Option<usize> {
+        let first = &self.games[0].inner;
+
+        if has_spy(first, player) {
+            Some(0)
+        } else if has_sniper(first, player) {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    /// Gets the wins and losses of a player in the match.
+    pub fn score(&self, player: &str) -> Option<(u32, u32)> {
+        self.position(player)
+            .map(|position| (self.wins[position], self.wins[1 - position]))
+    }
+
+    /// Gets the result of the match for a player.
+    pub fn result(&self, player: &str) -> Option<MatchResult> {
+        self.score(player).map(|(wins, losses)| {
+            if wins > losses {
+                MatchResult::Win
+            } else if wins < losses {
+                MatchResult::Loss
+            } else {
+                MatchResult::Draw
+            }
+        })
+    }
+}
+
+/// Groups replays, which must already be sorted by start time, into matches.
+pub fn group(replays: &[MatchedReplay]) -> Vec<Match<'_>> {
+    group_indices(replays)
+        .into_iter()
+        .map(|indices| Match::new(indices.iter().map(|&i| &replays[i]).collect()))
+        .collect()
+}
+
+/// Keeps only the replays matched by a query, out of the matches which had the query's match
+/// result for its player. With `Query::whole_matches`, every game of a match is kept if any of them
+/// are matched.
+pub(crate) fn retain(replays: &mut Vec<MatchedReplay>, query: &Query) {
+    let mut keep = vec![false; replays.len()];
+
+    for indices in group_indices(replays) {
+        if let (Some(result), Some(player)) = (query.match_result, query.match_player()) {
+            let games = indices.iter().map(|&i| &replays[i]).collect();
+
+            if Match::new(games).result(player) != Some(result) {
+                continue;
+            }
+        }
+
+        let matched = indices
+            .iter()
+            .map(|&i| query.matches(&replays[i].inner))
+            .collect::<Vec<_>>();
+
+        if matched.iter().any(|&matched| matched) {
+            for (&i, matched) in indices.iter().zip(matched) {
+                keep[i] = matched || query.whole_matches;
+            }
+        }
+    }
+
+    let mut keep = keep.into_iter();
+    replays.retain(|_| keep.next().unwrap());
+}
+
+/// Groups replays into matches, returning the indices of the games in each match.
+fn group_indices(replays: &[MatchedReplay]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut current: HashMap<(&str, &str), usize> = HashMap::new();
+
+    for (i, replay) in replays.iter().enumerate() {
+        let pair = pair(&replay.inner);
+
+        match current.get(&pair) {
+            Some(&group)
+                if follows(
+                    &replays[*groups[group].last().unwrap()].inner,
+                    &replay.inner,
+                ) =>
+            {
+                groups[group].push(i);
+            }
+            _ => {
+                current.insert(pair, groups.len());
+                groups.push(vec![i]);
+            }
+        }
+    }
+
+    groups
+}
+
+/// Gets the user names of both players in a replay, in a consistent order regardless of role.
+fn pair(replay: &Replay) -> (&str, &str) {
+    let spy = replay.header.spy_user_name.as_str();
+    let sniper = replay.header.sniper_user_name.as_str();
+
+    if spy < sniper {
+        (spy, sniper)
+    } else {
+        (sniper, spy)
+    }
+}
+
+/// Checks if a game follows on from the previous game between the same pair of players in the
+/// same match, which has to have a higher play ID and start soon after the previous game ended.
+fn follows(previous: &Replay, replay: &Replay) -> bool {
+    let previous_end = previous.header.start_time as f32 + previous.header.duration;
+
+    replay.header.play_id > previous.header.play_id
+        && replay.header.start_time as f32 - previous_end <= MAX_GAP
+}
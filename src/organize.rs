@@ -0,0 +1,119 @@
+use crate::errors::*;
+use crate::output::group_by::Dimension;
+use crate::MatchedReplay;
+use log::warn;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What to do with each replay when organizing them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    /// Copy the replay into its folder, leaving the original where it is.
+    Copy,
+    /// Move the replay into its folder.
+    Move,
+    /// Print where the replay would go without touching any files.
+    DryRun,
+}
+
+/// Gets the path a replay should be organized to, inside a folder for the value of each dimension
+/// nested in order.
+pub fn destination(replay: &MatchedReplay, root: &Path, dimensions: &[Dimension]) -> PathBuf {
+    let mut path = root.to_path_buf();
+
+    for dimension in dimensions {
+        path.push(folder_name(&dimension.value(&replay.inner)));
+    }
+
+    if let Some(file_name) = Path::new(&replay.path).file_name() {
+        path.push(file_name);
+    }
+
+    path
+}
+
+/// Copies or moves each replay into a folder for the values of the dimensions, returning the number
+/// of replays organized.
+///
+/// Replays which already exist at their destination are skipped with a warning, so running it
+/// again on the same replays never overwrites anything.
+pub fn organize(
+    replays: &[MatchedReplay],
+    root: &Path,
+    dimensions: &[Dimension],
+    action: Action,
+) -> Result<usize> {
+    let mut organized = 0;
+
+    for replay in replays {
+        let source = Path::new(&replay.path);
+        let target = destination(replay, root, dimensions);
+
+        if target.exists() {
+            warn!("'{}' already exists, skipping", target.display());
+            continue;
+        }
+
+        if action == Action::DryRun {
+            println!("{} -> {}", source.display(), target.display());
+            organized += 1;
+            continue;
+        }
+
+        if let Some(folder) = target.parent() {
+            fs::create_dir_all(folder)
+                .chain_err(|| format!("failed to create directory '{}'", folder.display()))?;
+        }
+
+        match action {
+            Action::Move => move_file(source, &target),
+            _ => copy_file(source, &target),
+        }?;
+
+        organized += 1;
+    }
+
+    Ok(organized)
+}
+
+/// Copies a replay to a new path.
+fn copy_file(source: &Path, target: &Path) -> Result<()> {
+    fs::copy(source, target).map(|_| ()).chain_err(|| {
+        format!(
+            "failed to copy '{}' to '{}'",
+            source.display(),
+            target.display()
+        )
+    })
+}
+
+/// Moves a replay to a new path, falling back to copying and removing it when it can't be renamed
+/// (e.g. when moving to a different drive).
+fn move_file(source: &Path, target: &Path) -> Result<()> {
+    if fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+
+    copy_file(source, target)?;
+    fs::remove_file(source).chain_err(|| format!("failed to remove '{}'", source.display()))
+}
+
+/// Turns the value of a dimension into a folder name which is valid on every platform, replacing
+/// any reserved characters (such as the slash in "player/steam").
+pub fn folder_name(value: &str) -> String {
+    let name: String = value
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let name = name.trim().trim_end_matches('.');
+
+    if name.is_empty() {
+        "_".to_string()
+    } else {
+        name.to_string()
+    }
+}
@@ -1,25 +1,98 @@
 use crate::errors::*;
+use crate::output::group_by::Dimension;
+use crate::output::lines::LineFormat;
+use crate::output::sheet::Template;
+use crate::output::summary::{SummaryFormat, SummaryOptions};
+use crate::output::timeline::Period;
+use crate::query::Query;
+use crate::ratings::RatingSystem;
 use crate::MatchedReplay;
-use clap::ArgMatches;
 
 pub mod csv;
+pub mod form;
+pub mod group_by;
+pub mod head_to_head;
+pub mod json;
+pub mod lines;
+pub mod matches;
+pub mod ratings;
+pub mod scout;
+pub mod sheet;
 pub mod summary;
-pub mod table;
+pub mod timeline;
+
+/// The different ways the matched replays can be shown.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputMode {
+    /// Just the number of matched replays.
+    Count,
+    /// The paths of the matched replays.
+    Paths,
+    /// A line of text about each of the matched replays, filled in from a template.
+    Lines(LineFormat),
+    /// The matched replays in a verbose CSV format.
+    Csv,
+    /// The matched replays as a JSON array.
+    Json,
+    /// The matched replays as newline delimited JSON, with one object per line.
+    Ndjson,
+    /// A matrix of the wins and losses between a set of players.
+    HeadToHead(TableFormat),
+    /// Every match in the replays along with its score.
+    Matches(TableFormat),
+    /// The number of games and spy win rate for each combination of values of a set of
+    /// dimensions, such as each map and spy.
+    GroupBy(Vec<Dimension>, TableFormat),
+    /// The streaks and rolling win rate over a number of recent games of each player.
+    Form(usize, TableFormat),
+    /// The number of games, spy win rate, results and average duration of each day, week or
+    /// month.
+    Timeline(Period, TableFormat),
+    /// A leaderboard of player ratings.
+    Ratings(RatingSystem, TableFormat),
+    /// The ratings of both players after every game, in a CSV format.
+    RatingHistory(RatingSystem),
+    /// Tables of the games of a single player in a CSV format, laid out by a template for use in a
+    /// spreadsheet.
+    Sheet(Template),
+    /// A report on a single player, with stats from the role they played in each game.
+    Scout(SummaryFormat),
+    /// A summary of stats about the matched replays, in a particular format.
+    Summary(SummaryFormat, SummaryOptions),
+}
+
+/// The different formats a table can be shown in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableFormat {
+    /// A table drawn with text, meant to be read in a terminal.
+    Text,
+    /// CSV, for use in spreadsheets.
+    Csv,
+}
 
 /// Prints various representations of the filtered replays.
-pub fn show(replays: &[MatchedReplay], matches: &ArgMatches) -> Result<()> {
-    if matches.is_present("count") {
-        println!("{}", replays.len());
-    } else if matches.is_present("show-paths") {
-        for replay in replays {
-            println!("{}", replay.path);
+pub fn show(replays: &[MatchedReplay], query: &Query, mode: OutputMode) -> Result<()> {
+    match mode {
+        OutputMode::Count => println!("{}", replays.len()),
+        OutputMode::Paths => {
+            for replay in replays {
+                println!("{}", replay.path);
+            }
         }
-    } else if matches.is_present("csv") {
-        csv::show(replays)?;
-    } else if matches.is_present("special-csv") {
-        table::show(replays, matches)?;
-    } else {
-        summary::show(replays, matches);
+        OutputMode::Lines(format) => lines::show(replays, &format)?,
+        OutputMode::Csv => csv::show(replays)?,
+        OutputMode::Json => json::show(replays)?,
+        OutputMode::Ndjson => json::show_lines(replays)?,
+        OutputMode::HeadToHead(format) => head_to_head::show(replays, query, format)?,
+        OutputMode::Matches(format) => matches::show(replays, query, format)?,
+        OutputMode::GroupBy(dimensions, format) => group_by::show(replays, &dimensions, format)?,
+        OutputMode::Form(games, format) => form::show(replays, query, games, format)?,
+        OutputMode::Timeline(period, format) => timeline::show(replays, period, format)?,
+        OutputMode::Ratings(system, format) => ratings::show(replays, system, format)?,
+        OutputMode::RatingHistory(system) => ratings::show_history(replays, system)?,
+        OutputMode::Sheet(template) => sheet::show(replays, query, &template)?,
+        OutputMode::Scout(format) => scout::show(replays, query, format)?,
+        OutputMode::Summary(format, options) => summary::show(replays, query, format, options)?,
     }
 
     Ok(())
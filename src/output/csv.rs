@@ -1,12 +1,13 @@
 use crate::errors::*;
+use crate::utils::{map_variant_to_string, mode_to_string};
 use crate::MatchedReplay;
-use spyparty::{GameMode, MapVariant, Mission};
+use spyparty::Mission;
 
 /// Outputs almost all the data in the replay header in a CSV format.
 pub fn show(replays: &[MatchedReplay]) -> Result<()> {
     let mut wtr = csv::Writer::from_writer(std::io::stdout());
 
-    wtr.write_record(&[
+    wtr.write_record([
         "replay_version",
         "protocol_version",
         "spyparty_version",
@@ -37,7 +38,7 @@ pub fn show(replays: &[MatchedReplay]) -> Result<()> {
         let header = &replay.inner.header;
         let result_data = &header.result_data;
 
-        wtr.write_record(&[
+        wtr.write_record([
             &header.replay_version.to_string(),
             &header.protocol_version.to_string(),
             &header.spyparty_version.to_string(),
@@ -47,9 +48,9 @@ pub fn show(replays: &[MatchedReplay]) -> Result<()> {
             &header.play_id.to_string(),
             &header.latency.to_string(),
             &header.spy_user_name,
-            &replay.inner.spy_name(),
+            replay.inner.spy_name(),
             &header.sniper_user_name,
-            &replay.inner.sniper_name(),
+            replay.inner.sniper_name(),
             &match result_data.simple_rules {
                 Some(simple_rules) => simple_rules.to_string(),
                 None => "".to_string(),
@@ -57,10 +58,7 @@ pub fn show(replays: &[MatchedReplay]) -> Result<()> {
             &format!("{:?}", result_data.game_result),
             &mode_to_string(&result_data.game_mode),
             &format!("{}", result_data.map),
-            &match &result_data.map_variant {
-                MapVariant::Teien(variant) => format!("{:?}", variant),
-                MapVariant::None => "None".to_string(),
-            },
+            &map_variant_to_string(&result_data.map_variant),
             &join_missions(&result_data.selected_missions),
             &join_missions(&result_data.picked_missions),
             &join_missions(&result_data.completed_missions),
@@ -89,12 +87,3 @@ fn join_missions(missions: &[Mission]) -> String {
         .collect::<Vec<String>>()
         .join(",")
 }
-
-/// Convert a game mode into its short version.
-fn mode_to_string(mode: &GameMode) -> String {
-    match mode {
-        GameMode::Known(x) => format!("k{}", x),
-        GameMode::Pick(x, y) => format!("p{}/{}", x, y),
-        GameMode::Any(x, y) => format!("a{}/{}", x, y),
-    }
-}
@@ -0,0 +1,95 @@
+use crate::errors::*;
+use crate::form::{self, RoleForm};
+use crate::output::TableFormat;
+use crate::query::Query;
+use crate::utils::percentage;
+use crate::MatchedReplay;
+use prettytable::{Cell, Row, Table};
+
+/// Shows the streaks and recent win rate of each of the players in the query, in each role.
+pub fn show(
+    replays: &[MatchedReplay],
+    query: &Query,
+    games: usize,
+    format: TableFormat,
+) -> Result<()> {
+    let players = query.named_players();
+
+    if players.is_empty() {
+        bail!(
+            "form needs at least one player, passed with --players, --pair, --spies or --snipers"
+        );
+    }
+
+    let mut table = Table::new();
+    let mut header = Row::empty();
+    for title in &["PLAYER", "ROLE", "GAMES", "RECORD", "WIN %"] {
+        header.add_cell(Cell::new(title));
+    }
+    header.add_cell(Cell::new(&format!("LAST {} WIN %", games)));
+    for title in &["STREAK", "LONGEST WIN STREAK", "LONGEST LOSS STREAK"] {
+        header.add_cell(Cell::new(title));
+    }
+    table.add_row(header);
+
+    for player in form::calculate(replays, &players, games) {
+        let roles = [
+            ("Spy", &player.spy),
+            ("Sniper", &player.sniper),
+            ("Overall", &player.overall),
+        ];
+
+        for (role, role_form) in &roles {
+            if role_form.games() > 0 {
+                table.add_row(form_row(&player.display_name, role, role_form));
+            }
+        }
+    }
+
+    match format {
+        TableFormat::Text => {
+            table.printstd();
+        }
+        TableFormat::Csv => {
+            table
+                .to_csv(std::io::stdout())
+                .chain_err(|| "failed to write form table")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates a row of the table for a player in a particular role.
+fn form_row(name: &str, role: &str, form: &RoleForm) -> Row {
+    let mut row = Row::empty();
+
+    row.add_cell(Cell::new(name));
+    row.add_cell(Cell::new(role));
+    row.add_cell(Cell::new(&form.games().to_string()));
+    row.add_cell(Cell::new(&format!("{}-{}", form.wins, form.losses)));
+    row.add_cell(Cell::new(&format!(
+        "{:.1}",
+        percentage(form.wins, form.games())
+    )));
+    row.add_cell(Cell::new(&match form.recent_win_percentage() {
+        Some(recent) => format!("{:.1}", recent),
+        None => "-".to_string(),
+    }));
+    row.add_cell(Cell::new(&streak(form.current_streak)));
+    row.add_cell(Cell::new(&form.longest_win_streak.to_string()));
+    row.add_cell(Cell::new(&form.longest_loss_streak.to_string()));
+
+    row
+}
+
+/// Shows a streak as the number of wins ("W3") or losses ("L2") in a row.
+pub(crate) fn streak(streak: i32) -> String {
+    if streak > 0 {
+        format!("W{}", streak)
+    } else if streak < 0 {
+        format!("L{}", -streak)
+    } else {
+        "-".to_string()
+    }
+}
@@ -0,0 +1,160 @@
+use crate::errors::*;
+use crate::output::summary::{map_name, result_name};
+use crate::output::TableFormat;
+use crate::utils::{mode_to_string, percentage, start_time};
+use crate::MatchedReplay;
+use prettytable::{Cell, Row, Table};
+use spyparty::Replay;
+use std::collections::BTreeMap;
+
+/// Something about a game that replays can be grouped by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dimension {
+    Map,
+    Mode,
+    Result,
+    /// The user name of the spy, which stays the same when their display name changes.
+    Spy,
+    /// The user name of the sniper.
+    Sniper,
+    /// The month the game started in.
+    Month,
+    /// The set of missions completed.
+    MissionSet,
+    /// The version of SpyParty the game was played on.
+    Version,
+}
+
+/// The number of games in a group and how many of them were won by the spy.
+#[derive(Clone, Copy, Default)]
+pub struct GroupStats {
+    pub games: u32,
+    pub spy_wins: u32,
+}
+
+impl Dimension {
+    /// Parses the name of a dimension.
+    pub fn parse(value: &str) -> Result<Dimension> {
+        let dimension = match value.to_ascii_lowercase().as_str() {
+            "map" => Dimension::Map,
+            "mode" => Dimension::Mode,
+            "result" => Dimension::Result,
+            "spy" => Dimension::Spy,
+            "sniper" => Dimension::Sniper,
+            "month" => Dimension::Month,
+            "mission-set" => Dimension::MissionSet,
+            "version" => Dimension::Version,
+            _ => bail!("'{}' is not a valid option to group by", value),
+        };
+
+        Ok(dimension)
+    }
+
+    /// The title of a column for the dimension.
+    fn title(self) -> &'static str {
+        match self {
+            Dimension::Map => "MAP",
+            Dimension::Mode => "MODE",
+            Dimension::Result => "RESULT",
+            Dimension::Spy => "SPY",
+            Dimension::Sniper => "SNIPER",
+            Dimension::Month => "MONTH",
+            Dimension::MissionSet => "MISSION SET",
+            Dimension::Version => "VERSION",
+        }
+    }
+
+    /// Gets the value of the dimension for a replay.
+    pub fn value(self, replay: &Replay) -> String {
+        let result_data = &replay.header.result_data;
+
+        match self {
+            Dimension::Map => map_name(&result_data.map).to_string(),
+            Dimension::Mode => mode_to_string(&result_data.game_mode),
+            Dimension::Result => result_name(&result_data.game_result).to_string(),
+            Dimension::Spy => replay.header.spy_user_name.clone(),
+            Dimension::Sniper => replay.header.sniper_user_name.clone(),
+            Dimension::Month => start_time(replay).format("%Y-%m").to_string(),
+            Dimension::MissionSet if result_data.completed_missions.is_empty() => {
+                "None".to_string()
+            }
+            Dimension::MissionSet => result_data
+                .completed_missions
+                .iter()
+                .map(|mission| mission.short_display())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Dimension::Version => replay.header.spyparty_version.to_string(),
+        }
+    }
+}
+
+/// Groups replays by the values of a set of dimensions, sorted by those values.
+pub fn group(
+    replays: &[MatchedReplay],
+    dimensions: &[Dimension],
+) -> BTreeMap<Vec<String>, GroupStats> {
+    let mut groups: BTreeMap<Vec<String>, GroupStats> = BTreeMap::new();
+
+    for replay in replays {
+        let key = dimensions
+            .iter()
+            .map(|dimension| dimension.value(&replay.inner))
+            .collect();
+        let stats = groups.entry(key).or_default();
+
+        stats.games += 1;
+        if replay.inner.is_spy_win() {
+            stats.spy_wins += 1;
+        }
+    }
+
+    groups
+}
+
+/// Shows the number of games and spy win rate for each combination of values of the dimensions.
+pub fn show(
+    replays: &[MatchedReplay],
+    dimensions: &[Dimension],
+    format: TableFormat,
+) -> Result<()> {
+    let mut table = Table::new();
+    let mut header = Row::empty();
+
+    for dimension in dimensions {
+        header.add_cell(Cell::new(dimension.title()));
+    }
+    header.add_cell(Cell::new("GAMES"));
+    header.add_cell(Cell::new("SPY WINS"));
+    header.add_cell(Cell::new("SPY WIN %"));
+    table.add_row(header);
+
+    for (values, stats) in group(replays, dimensions) {
+        let mut row = Row::empty();
+
+        for value in &values {
+            row.add_cell(Cell::new(value));
+        }
+        row.add_cell(Cell::new(&stats.games.to_string()));
+        row.add_cell(Cell::new(&stats.spy_wins.to_string()));
+        row.add_cell(Cell::new(&format!(
+            "{:.1}",
+            percentage(stats.spy_wins, stats.games)
+        )));
+
+        table.add_row(row);
+    }
+
+    match format {
+        TableFormat::Text => {
+            table.printstd();
+        }
+        TableFormat::Csv => {
+            table
+                .to_csv(std::io::stdout())
+                .chain_err(|| "failed to write group table")?;
+        }
+    }
+
+    Ok(())
+}
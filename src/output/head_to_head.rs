@@ -0,0 +1,230 @@
+use crate::errors::*;
+use crate::output::TableFormat;
+use crate::query::Query;
+use crate::utils::{has_sniper, has_spy};
+use crate::MatchedReplay;
+use prettytable::{Cell, Row, Table};
+use spyparty::Replay;
+use std::collections::HashMap;
+
+/// The wins and losses of one player against another, split by role.
+#[derive(Clone, Copy, Default)]
+pub struct Record {
+    pub spy_wins: u32,
+    pub spy_losses: u32,
+    pub sniper_wins: u32,
+    pub sniper_losses: u32,
+}
+
+/// The roles a head-to-head matrix can be shown for.
+#[derive(Clone, Copy)]
+pub enum Role {
+    Spy,
+    Sniper,
+    Both,
+}
+
+/// The wins and losses between every ordered pair of a set of players.
+pub struct HeadToHead {
+    pub players: Vec<String>,
+    records: HashMap<(usize, usize), Record>,
+}
+
+impl Record {
+    /// Gets the wins and losses in a particular role.
+    pub fn get(&self, role: Role) -> (u32, u32) {
+        match role {
+            Role::Spy => (self.spy_wins, self.spy_losses),
+            Role::Sniper => (self.sniper_wins, self.sniper_losses),
+            Role::Both => (
+                self.spy_wins + self.sniper_wins,
+                self.spy_losses + self.sniper_losses,
+            ),
+        }
+    }
+
+    /// Adds another record to this one.
+    fn add(&mut self, other: &Record) {
+        self.spy_wins += other.spy_wins;
+        self.spy_losses += other.spy_losses;
+        self.sniper_wins += other.sniper_wins;
+        self.sniper_losses += other.sniper_losses;
+    }
+}
+
+impl Role {
+    /// A readable title for the role.
+    fn title(self) -> &'static str {
+        match self {
+            Role::Spy => "As Spy",
+            Role::Sniper => "As Sniper",
+            Role::Both => "Overall",
+        }
+    }
+
+    /// A short name for the role, used in CSV output.
+    fn name(self) -> &'static str {
+        match self {
+            Role::Spy => "spy",
+            Role::Sniper => "sniper",
+            Role::Both => "overall",
+        }
+    }
+}
+
+impl HeadToHead {
+    /// Tallies up the games played between a set of players.
+    ///
+    /// Only games where both the spy and the sniper are in the set are counted.
+    pub fn new(players: &[String], replays: &[MatchedReplay]) -> Self {
+        let mut head_to_head = HeadToHead {
+            players: players.to_vec(),
+            records: HashMap::new(),
+        };
+
+        for replay in replays {
+            head_to_head.aggregate(&replay.inner);
+        }
+
+        head_to_head
+    }
+
+    /// Gets the record of a player against an opponent, from the player's point of view.
+    pub fn record(&self, player: usize, opponent: usize) -> Record {
+        self.records
+            .get(&(player, opponent))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Gets the record of a player against all the other players in the set.
+    pub fn total(&self, player: usize) -> Record {
+        let mut total = Record::default();
+
+        for opponent in 0..self.players.len() {
+            total.add(&self.record(player, opponent));
+        }
+
+        total
+    }
+
+    /// Adds the result of a replay to the records of both players.
+    fn aggregate(&mut self, replay: &Replay) {
+        let spy = self.players.iter().position(|name| has_spy(replay, name));
+        let sniper = self
+            .players
+            .iter()
+            .position(|name| has_sniper(replay, name));
+
+        let (spy, sniper) = match (spy, sniper) {
+            (Some(spy), Some(sniper)) if spy != sniper => (spy, sniper),
+            _ => return,
+        };
+
+        let spy_record = self.records.entry((spy, sniper)).or_default();
+        if replay.is_spy_win() {
+            spy_record.spy_wins += 1;
+        } else if replay.is_sniper_win() {
+            spy_record.spy_losses += 1;
+        }
+
+        let sniper_record = self.records.entry((sniper, spy)).or_default();
+        if replay.is_sniper_win() {
+            sniper_record.sniper_wins += 1;
+        } else if replay.is_spy_win() {
+            sniper_record.sniper_losses += 1;
+        }
+    }
+
+    /// Creates a table with a row for each player and a column for each opponent, followed by
+    /// the player's total.
+    fn table(&self, role: Role, with_role: bool) -> Table {
+        let mut table = Table::new();
+        let mut header = Row::empty();
+
+        if with_role {
+            header.add_cell(Cell::new("ROLE"));
+        }
+        header.add_cell(Cell::new("PLAYER"));
+        for opponent in &self.players {
+            header.add_cell(Cell::new(opponent));
+        }
+        header.add_cell(Cell::new("TOTAL"));
+        table.add_row(header);
+
+        for (player, name) in self.players.iter().enumerate() {
+            let mut row = Row::empty();
+
+            if with_role {
+                row.add_cell(Cell::new(role.name()));
+            }
+            row.add_cell(Cell::new(name));
+
+            for opponent in 0..self.players.len() {
+                if player == opponent {
+                    row.add_cell(Cell::new("-"));
+                } else {
+                    row.add_cell(Cell::new(&win_loss(
+                        self.record(player, opponent).get(role),
+                    )));
+                }
+            }
+
+            row.add_cell(Cell::new(&win_loss(self.total(player).get(role))));
+            table.add_row(row);
+        }
+
+        table
+    }
+}
+
+/// Shows a matrix of the wins and losses between each of the players in the query, split by
+/// role.
+///
+/// Each cell is the record of the player in that row against the player in that column.
+pub fn show(replays: &[MatchedReplay], query: &Query, format: TableFormat) -> Result<()> {
+    if query.players.len() < 2 {
+        bail!("head-to-head requires at least two players to be passed with --players");
+    }
+
+    let head_to_head = HeadToHead::new(&query.players, replays);
+    let roles = [Role::Spy, Role::Sniper, Role::Both];
+
+    match format {
+        TableFormat::Text => {
+            for (i, role) in roles.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                println!("{}:", role.title());
+                head_to_head.table(*role, false).printstd();
+            }
+        }
+        TableFormat::Csv => {
+            let mut table = Table::new();
+
+            for (i, role) in roles.iter().enumerate() {
+                // Only keep the header of the first table
+                for row in
+                    head_to_head
+                        .table(*role, true)
+                        .row_iter()
+                        .skip(if i > 0 { 1 } else { 0 })
+                {
+                    table.add_row(row.clone());
+                }
+            }
+
+            table
+                .to_csv(std::io::stdout())
+                .chain_err(|| "failed to write head-to-head table")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Formats a number of wins and losses.
+fn win_loss((wins, losses): (u32, u32)) -> String {
+    format!("{}-{}", wins, losses)
+}
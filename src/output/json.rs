@@ -0,0 +1,130 @@
+use crate::errors::*;
+use crate::utils::{self, map_variant_to_string, mode_to_string};
+use crate::MatchedReplay;
+use serde::Serialize;
+use spyparty::Mission;
+use std::io::{self, BufWriter, Write};
+
+/// A matched replay in a form which can be serialized, containing all the data in the replay
+/// header along with some useful derived values.
+#[derive(Serialize)]
+pub struct ReplayRecord<'a> {
+    pub path: &'a str,
+    pub replay_version: u32,
+    pub protocol_version: u32,
+    pub spyparty_version: u32,
+    pub duration: f32,
+    pub game_id: String,
+    pub start_time: u32,
+    pub play_id: u16,
+    pub latency: f32,
+    pub spy_user_name: &'a str,
+    pub spy_display_name: Option<&'a str>,
+    pub sniper_user_name: &'a str,
+    pub sniper_display_name: Option<&'a str>,
+    pub result_data: ResultDataRecord,
+    /// The role which won the game, either "spy" or "sniper", or none if the game didn't finish.
+    pub winner_role: Option<&'static str>,
+    /// The display name of the player who won the game.
+    pub winner: Option<String>,
+    /// The fraction of the starting clock that was used.
+    pub clock_usage: Option<f32>,
+}
+
+/// The result data of a replay in a form which can be serialized.
+#[derive(Serialize)]
+pub struct ResultDataRecord {
+    pub simple_rules: Option<bool>,
+    pub result: String,
+    pub mode: String,
+    pub map: String,
+    pub map_variant: String,
+    pub selected_missions: Vec<String>,
+    pub picked_missions: Vec<String>,
+    pub completed_missions: Vec<String>,
+    pub completed_missions_hash: u32,
+    pub guests: Option<u32>,
+    pub clock_start: Option<u32>,
+}
+
+impl<'a> ReplayRecord<'a> {
+    /// Creates a record from a matched replay.
+    pub fn new(replay: &'a MatchedReplay) -> Self {
+        let header = &replay.inner.header;
+        let result_data = &header.result_data;
+
+        let (winner_role, winner) = if replay.inner.is_spy_win() {
+            (Some("spy"), Some(replay.inner.spy_name().clone()))
+        } else if replay.inner.is_sniper_win() {
+            (Some("sniper"), Some(replay.inner.sniper_name().clone()))
+        } else {
+            (None, None)
+        };
+
+        ReplayRecord {
+            path: &replay.path,
+            replay_version: header.replay_version,
+            protocol_version: header.protocol_version,
+            spyparty_version: header.spyparty_version,
+            duration: header.duration,
+            game_id: format!("{:x}", header.game_id),
+            start_time: header.start_time,
+            play_id: header.play_id,
+            latency: header.latency,
+            spy_user_name: &header.spy_user_name,
+            spy_display_name: header.spy_display_name.as_deref(),
+            sniper_user_name: &header.sniper_user_name,
+            sniper_display_name: header.sniper_display_name.as_deref(),
+            result_data: ResultDataRecord {
+                simple_rules: result_data.simple_rules,
+                result: format!("{:?}", result_data.game_result),
+                mode: mode_to_string(&result_data.game_mode),
+                map: format!("{}", result_data.map),
+                map_variant: map_variant_to_string(&result_data.map_variant),
+                selected_missions: mission_names(&result_data.selected_missions),
+                picked_missions: mission_names(&result_data.picked_missions),
+                completed_missions: mission_names(&result_data.completed_missions),
+                completed_missions_hash: result_data.completed_missions_raw,
+                guests: result_data.guests,
+                clock_start: result_data.clock_start,
+            },
+            winner_role,
+            winner,
+            clock_usage: utils::clock_usage(&replay.inner),
+        }
+    }
+}
+
+/// Outputs all the data in the replay headers as a single JSON array.
+pub fn show(replays: &[MatchedReplay]) -> Result<()> {
+    let records = replays.iter().map(ReplayRecord::new).collect::<Vec<_>>();
+    let mut out = BufWriter::new(io::stdout());
+
+    serde_json::to_writer_pretty(&mut out, &records).chain_err(|| "failed to write JSON")?;
+    writeln!(out).chain_err(|| "failed to write to stdout")?;
+    out.flush().chain_err(|| "failed to write to stdout")?;
+    Ok(())
+}
+
+/// Outputs all the data in the replay headers as newline delimited JSON, writing one object per
+/// line as each replay is converted.
+pub fn show_lines(replays: &[MatchedReplay]) -> Result<()> {
+    let mut out = BufWriter::new(io::stdout());
+
+    for replay in replays {
+        serde_json::to_writer(&mut out, &ReplayRecord::new(replay))
+            .chain_err(|| "failed to write JSON")?;
+        writeln!(out).chain_err(|| "failed to write to stdout")?;
+    }
+
+    out.flush().chain_err(|| "failed to write to stdout")?;
+    Ok(())
+}
+
+/// Gets the names of a list of missions.
+fn mission_names(missions: &[Mission]) -> Vec<String> {
+    missions
+        .iter()
+        .map(|mission| format!("{}", mission))
+        .collect()
+}
@@ -0,0 +1,337 @@
+use crate::errors::*;
+use crate::output::summary::{map_name, result_name};
+use crate::utils::{clock_usage, map_variant_to_string, mode_to_string, start_time};
+use crate::MatchedReplay;
+use chrono::format::{Item, StrftimeItems};
+use spyparty::Mission;
+use std::io::{self, BufWriter, Write};
+
+/// The format of the start time when a field doesn't give one.
+const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// A template for a line of text about each replay, such as
+/// `{start_time:%Y-%m-%d} {spy} vs {sniper} on {map}: {result}`.
+///
+/// Fields are written in braces, with an optional format after a colon, and literal braces are
+/// written twice (`{{` and `}}`). The template is checked when it is parsed, so unknown fields and
+/// invalid formats are caught before any replays are read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineFormat {
+    parts: Vec<Part>,
+}
+
+/// A piece of a line template.
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Field(Field, Option<String>),
+}
+
+/// A value about a replay which can be used in a line template.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+    Path,
+    ReplayVersion,
+    ProtocolVersion,
+    SpypartyVersion,
+    /// The length of the game, shown like "1m20s", or in seconds with a precision (e.g. `.1`).
+    Duration,
+    GameId,
+    /// The local time the game started, in a strftime format (e.g. `%Y-%m-%d`).
+    StartTime,
+    PlayId,
+    Latency,
+    /// The display name of the spy.
+    Spy,
+    SpyUserName,
+    /// The display name of the sniper.
+    Sniper,
+    SniperUserName,
+    SimpleRules,
+    Result,
+    Mode,
+    Map,
+    MapVariant,
+    /// The missions on the map, in full or with `short` names.
+    SelectedMissions,
+    PickedMissions,
+    CompletedMissions,
+    CompletedMissionsHash,
+    Guests,
+    ClockStart,
+    /// The display name of the player who won, which is empty if the game didn't finish.
+    Winner,
+    /// The display name of the player who lost.
+    Loser,
+    /// The role which won, either "spy" or "sniper".
+    WinnerRole,
+    /// The fraction of the starting clock that was used, with an optional precision.
+    ClockUsage,
+}
+
+impl Field {
+    /// The names of every field, as they are written in a template.
+    const NAMES: [&'static str; 28] = [
+        "path",
+        "replay_version",
+        "protocol_version",
+        "spyparty_version",
+        "duration",
+        "game_id",
+        "start_time",
+        "play_id",
+        "latency",
+        "spy",
+        "spy_user_name",
+        "sniper",
+        "sniper_user_name",
+        "simple_rules",
+        "result",
+        "mode",
+        "map",
+        "map_variant",
+        "selected_missions",
+        "picked_missions",
+        "completed_missions",
+        "completed_missions_hash",
+        "guests",
+        "clock_start",
+        "winner",
+        "loser",
+        "winner_role",
+        "clock_usage",
+    ];
+
+    /// Parses the name of a field. The display names of the players can also be written the same
+    /// way as in the JSON output (e.g. `spy_display_name`).
+    pub fn parse(value: &str) -> Result<Field> {
+        let field = match value {
+            "path" => Field::Path,
+            "replay_version" => Field::ReplayVersion,
+            "protocol_version" => Field::ProtocolVersion,
+            "spyparty_version" => Field::SpypartyVersion,
+            "duration" => Field::Duration,
+            "game_id" => Field::GameId,
+            "start_time" => Field::StartTime,
+            "play_id" => Field::PlayId,
+            "latency" => Field::Latency,
+            "spy" | "spy_display_name" => Field::Spy,
+            "spy_user_name" => Field::SpyUserName,
+            "sniper" | "sniper_display_name" => Field::Sniper,
+            "sniper_user_name" => Field::SniperUserName,
+            "simple_rules" => Field::SimpleRules,
+            "result" => Field::Result,
+            "mode" => Field::Mode,
+            "map" => Field::Map,
+            "map_variant" => Field::MapVariant,
+            "selected_missions" => Field::SelectedMissions,
+            "picked_missions" => Field::PickedMissions,
+            "completed_missions" => Field::CompletedMissions,
+            "completed_missions_hash" => Field::CompletedMissionsHash,
+            "guests" => Field::Guests,
+            "clock_start" => Field::ClockStart,
+            "winner" => Field::Winner,
+            "loser" => Field::Loser,
+            "winner_role" => Field::WinnerRole,
+            "clock_usage" => Field::ClockUsage,
+            _ => bail!(
+                "'{}' is not a valid field (expected one of {})",
+                value,
+                Field::NAMES.join(", ")
+            ),
+        };
+
+        Ok(field)
+    }
+
+    /// Checks if a format can be used with the field.
+    fn accepts_format(self, format: &str) -> bool {
+        match self {
+            Field::StartTime => !StrftimeItems::new(format).any(|item| item == Item::Error),
+            Field::Duration | Field::Latency | Field::ClockUsage => precision(format).is_some(),
+            Field::SelectedMissions | Field::PickedMissions | Field::CompletedMissions => {
+                format == "short"
+            }
+            _ => false,
+        }
+    }
+
+    /// Gets the value of the field for a replay, in a format if there is one.
+    fn value(self, replay: &MatchedReplay, format: Option<&str>) -> String {
+        let inner = &replay.inner;
+        let header = &inner.header;
+        let result_data = &header.result_data;
+
+        match self {
+            Field::Path => replay.path.clone(),
+            Field::ReplayVersion => header.replay_version.to_string(),
+            Field::ProtocolVersion => header.protocol_version.to_string(),
+            Field::SpypartyVersion => header.spyparty_version.to_string(),
+            Field::Duration => match format.and_then(precision) {
+                Some(precision) => format!("{:.*}", precision, header.duration),
+                None => {
+                    let duration = header.duration as u32;
+                    format!("{}m{}s", duration / 60, duration % 60)
+                }
+            },
+            Field::GameId => format!("{:x}", header.game_id),
+            Field::StartTime => start_time(inner)
+                .format(format.unwrap_or(DEFAULT_TIME_FORMAT))
+                .to_string(),
+            Field::PlayId => header.play_id.to_string(),
+            Field::Latency => decimal(header.latency, format),
+            Field::Spy => inner.spy_name().clone(),
+            Field::SpyUserName => header.spy_user_name.clone(),
+            Field::Sniper => inner.sniper_name().clone(),
+            Field::SniperUserName => header.sniper_user_name.clone(),
+            Field::SimpleRules => optional(result_data.simple_rules),
+            Field::Result => result_name(&result_data.game_result).to_string(),
+            Field::Mode => mode_to_string(&result_data.game_mode),
+            Field::Map => map_name(&result_data.map).to_string(),
+            Field::MapVariant => map_variant_to_string(&result_data.map_variant),
+            Field::SelectedMissions => missions(&result_data.selected_missions, format),
+            Field::PickedMissions => missions(&result_data.picked_missions, format),
+            Field::CompletedMissions => missions(&result_data.completed_missions, format),
+            Field::CompletedMissionsHash => result_data.completed_missions_raw.to_string(),
+            Field::Guests => optional(result_data.guests),
+            Field::ClockStart => optional(result_data.clock_start),
+            Field::Winner if inner.is_spy_win() => inner.spy_name().clone(),
+            Field::Winner if inner.is_sniper_win() => inner.sniper_name().clone(),
+            Field::Loser if inner.is_spy_win() => inner.sniper_name().clone(),
+            Field::Loser if inner.is_sniper_win() => inner.spy_name().clone(),
+            Field::WinnerRole if inner.is_spy_win() => "spy".to_string(),
+            Field::WinnerRole if inner.is_sniper_win() => "sniper".to_string(),
+            Field::Winner | Field::Loser | Field::WinnerRole => String::new(),
+            Field::ClockUsage => clock_usage(inner)
+                .map(|clock_usage| decimal(clock_usage, format))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl LineFormat {
+    /// Parses a line template, checking every field and format in it.
+    pub fn parse(template: &str) -> Result<LineFormat> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => field.push(c),
+                            None => bail!("unclosed '{{' in template '{}'", template),
+                        }
+                    }
+
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(parse_field(&field)?);
+                }
+                '}' => bail!(
+                    "unmatched '}}' in template '{}' (write '}}}}' for a literal brace)",
+                    template
+                ),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(LineFormat { parts })
+    }
+
+    /// Fills in the template for a replay.
+    pub fn line(&self, replay: &MatchedReplay) -> String {
+        let mut line = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => line.push_str(text),
+                Part::Field(field, format) => {
+                    line.push_str(&field.value(replay, format.as_deref()));
+                }
+            }
+        }
+
+        line
+    }
+}
+
+/// Outputs a line for each replay, filled in from a template.
+pub fn show(replays: &[MatchedReplay], format: &LineFormat) -> Result<()> {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for replay in replays {
+        writeln!(out, "{}", format.line(replay)).chain_err(|| "failed to write line")?;
+    }
+
+    out.flush().chain_err(|| "failed to write lines")
+}
+
+/// Parses a field and its optional format, such as "start_time:%Y-%m-%d".
+fn parse_field(value: &str) -> Result<Part> {
+    let (name, format) = match value.find(':') {
+        Some(i) => (&value[..i], Some(&value[i + 1..])),
+        None => (value, None),
+    };
+    let name = name.trim();
+    let field = Field::parse(name)?;
+
+    if let Some(format) = format {
+        if !field.accepts_format(format) {
+            bail!("'{}' is not a valid format for {}", format, name);
+        }
+    }
+
+    Ok(Part::Field(field, format.map(String::from)))
+}
+
+/// Parses a precision format, such as ".2".
+fn precision(format: &str) -> Option<usize> {
+    format
+        .strip_prefix('.')
+        .and_then(|digits| digits.parse().ok())
+}
+
+/// Shows a decimal value with the precision of a format, or as it is.
+fn decimal(value: f32, format: Option<&str>) -> String {
+    match format.and_then(precision) {
+        Some(precision) => format!("{:.*}", precision, value),
+        None => value.to_string(),
+    }
+}
+
+/// Shows an optional value, which is empty if it is missing.
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Joins the names of a list of missions, which are shortened with the "short" format.
+fn missions(missions: &[Mission], format: Option<&str>) -> String {
+    missions
+        .iter()
+        .map(|mission| match format {
+            Some("short") => mission.short_display().to_string(),
+            _ => mission.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
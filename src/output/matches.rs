@@ -0,0 +1,48 @@
+use crate::errors::*;
+use crate::matches;
+use crate::output::TableFormat;
+use crate::query::Query;
+use crate::utils::start_time;
+use crate::MatchedReplay;
+use prettytable::{row, Table};
+
+/// Shows every match in the replays along with its score.
+///
+/// If the query has a player, each match is shown from their point of view.
+pub fn show(replays: &[MatchedReplay], query: &Query, format: TableFormat) -> Result<()> {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "MATCH", "START", "PLAYER", "OPPONENT", "SCORE", "GAMES"
+    ]);
+
+    for (i, game_match) in matches::group(replays).iter().enumerate() {
+        let player = query
+            .match_player()
+            .and_then(|player| game_match.position(player))
+            .unwrap_or(0);
+        let opponent = 1 - player;
+
+        table.add_row(row![
+            i + 1,
+            start_time(&game_match.games[0].inner).format("%Y-%m-%d %H:%M"),
+            game_match.display_names[player],
+            game_match.display_names[opponent],
+            format!("{}-{}", game_match.wins[player], game_match.wins[opponent]),
+            game_match.games.len(),
+        ]);
+    }
+
+    match format {
+        TableFormat::Text => {
+            table.printstd();
+        }
+        TableFormat::Csv => {
+            table
+                .to_csv(std::io::stdout())
+                .chain_err(|| "failed to write matches table")?;
+        }
+    }
+
+    Ok(())
+}
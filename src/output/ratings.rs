@@ -0,0 +1,113 @@
+use crate::errors::*;
+use crate::output::TableFormat;
+use crate::ratings::{Rating, RatingSystem, Ratings};
+use crate::MatchedReplay;
+use prettytable::{Cell, Row, Table};
+
+/// Shows a leaderboard of the ratings of every player in the replays.
+pub fn show(replays: &[MatchedReplay], system: RatingSystem, format: TableFormat) -> Result<()> {
+    let ratings = Ratings::calculate(replays, system);
+    let with_deviation = system == RatingSystem::Glicko2;
+    let mut table = Table::new();
+
+    let mut header = Row::empty();
+    header.add_cell(Cell::new("RANK"));
+    header.add_cell(Cell::new("PLAYER"));
+    for title in &["RATING", "SPY", "SNIPER"] {
+        header.add_cell(Cell::new(title));
+        if with_deviation {
+            header.add_cell(Cell::new(&format!("{} RD", title)));
+        }
+    }
+    header.add_cell(Cell::new("GAMES"));
+    table.add_row(header);
+
+    for (rank, player) in ratings.leaderboard().iter().enumerate() {
+        let mut row = Row::empty();
+
+        row.add_cell(Cell::new(&(rank + 1).to_string()));
+        row.add_cell(Cell::new(&player.display_name));
+        for rating in &[player.combined, player.spy, player.sniper] {
+            row.add_cell(Cell::new(&format!("{:.0}", rating.value)));
+            if with_deviation {
+                row.add_cell(Cell::new(&format!("{:.0}", rating.deviation)));
+            }
+        }
+        row.add_cell(Cell::new(&player.combined.games.to_string()));
+
+        table.add_row(row);
+    }
+
+    match format {
+        TableFormat::Text => {
+            table.printstd();
+        }
+        TableFormat::Csv => {
+            table
+                .to_csv(std::io::stdout())
+                .chain_err(|| "failed to write ratings table")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Outputs the ratings of both players after every game in a CSV format, for charting how the
+/// ratings changed over time.
+pub fn show_history(replays: &[MatchedReplay], system: RatingSystem) -> Result<()> {
+    let ratings = Ratings::calculate(replays, system);
+    let mut wtr = csv::Writer::from_writer(std::io::stdout());
+
+    wtr.write_record([
+        "start_time",
+        "path",
+        "spy",
+        "sniper",
+        "winner",
+        "spy_rating",
+        "spy_rating_deviation",
+        "sniper_rating",
+        "sniper_rating_deviation",
+        "spy_combined_rating",
+        "spy_combined_rating_deviation",
+        "sniper_combined_rating",
+        "sniper_combined_rating_deviation",
+    ])
+    .chain_err(|| "failed to write CSV header")?;
+
+    for change in &ratings.history {
+        let mut record = vec![
+            change.start_time.to_string(),
+            change.path.clone(),
+            change.spy.clone(),
+            change.sniper.clone(),
+            if change.spy_won { "spy" } else { "sniper" }.to_string(),
+        ];
+
+        for rating in &[
+            change.spy_rating,
+            change.sniper_rating,
+            change.spy_combined_rating,
+            change.sniper_combined_rating,
+        ] {
+            record.extend(rating_fields(rating, system));
+        }
+
+        wtr.write_record(&record)
+            .chain_err(|| "failed to write CSV record")?;
+    }
+
+    wtr.flush().chain_err(|| "failed to write to stdout")?;
+    Ok(())
+}
+
+/// Converts a rating into its value and deviation, leaving the deviation empty for Elo.
+fn rating_fields(rating: &Rating, system: RatingSystem) -> Vec<String> {
+    vec![
+        format!("{:.1}", rating.value),
+        match system {
+            RatingSystem::Elo => "".to_string(),
+            RatingSystem::Glicko2 => format!("{:.1}", rating.deviation),
+        },
+    ]
+}
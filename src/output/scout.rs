@@ -0,0 +1,253 @@
+use crate::errors::*;
+use crate::form::{self, DEFAULT_FORM_GAMES};
+use crate::output::summary::{
+    self, ClockStatCollection, FormStat, MapStatCollection, MapWinStatCollection,
+    MissionSetWinStatCollection, MissionStatCollection, PlayerStatCollection, ResultStatCollection,
+    Section, SectionStats, StatCollection, Summary, SummaryFormat, SummaryOptions,
+    UncompletedMissionStatCollection,
+};
+use crate::query::Query;
+use crate::utils::{has_sniper, has_spy, is_glob, name_key};
+use crate::MatchedReplay;
+use spyparty::Replay;
+use std::collections::BTreeMap;
+
+/// A stat collection which only adds up the games where the scouted player was in one role,
+/// renaming its section to match.
+struct RoleStats<C> {
+    stats: C,
+    player: String,
+    as_spy: bool,
+    key: &'static str,
+    title: &'static str,
+}
+
+impl<C: StatCollection> RoleStats<C> {
+    /// Scopes a collection to the games where the player was the spy.
+    fn spy(stats: C, player: &str, key: &'static str, title: &'static str) -> Self {
+        RoleStats {
+            stats,
+            player: player.to_string(),
+            as_spy: true,
+            key,
+            title,
+        }
+    }
+
+    /// Scopes a collection to the games where the player was the sniper.
+    fn sniper(stats: C, player: &str, key: &'static str, title: &'static str) -> Self {
+        RoleStats {
+            as_spy: false,
+            ..RoleStats::spy(stats, player, key, title)
+        }
+    }
+}
+
+impl<C: StatCollection> StatCollection for RoleStats<C> {
+    fn aggregate(&mut self, replay: &Replay, query: &Query) {
+        let in_role = if self.as_spy {
+            has_spy(replay, &self.player)
+        } else {
+            has_sniper(replay, &self.player)
+        };
+
+        if in_role {
+            self.stats.aggregate(replay, query);
+        }
+    }
+
+    fn section(&self) -> Section {
+        let mut section = self.stats.section();
+        section.key = self.key;
+        section.title = self.title;
+        section
+    }
+}
+
+/// Shows a report on the player being scouted: their record, maps and missions as the spy,
+/// results and clock usage in each role, win rates on each map and recent form.
+pub fn show(replays: &[MatchedReplay], query: &Query, format: SummaryFormat) -> Result<()> {
+    let player = match query.players.first() {
+        Some(player) if is_glob(player) => matching_player(replays, query, player)?,
+        Some(player) if query.players.len() == 1 => player.clone(),
+        _ => bail!("scouting needs exactly one player"),
+    };
+
+    summary::render(&scout(replays, query, &player), format)
+}
+
+/// Finds the one player matched by a glob pattern, failing if it matches more than one of the
+/// players in the matched replays. Aliases add the identities that the pattern matches to the
+/// players in the query, so their user names are matched too.
+fn matching_player(replays: &[MatchedReplay], query: &Query, pattern: &str) -> Result<String> {
+    let mut players = BTreeMap::new();
+
+    for replay in replays {
+        let header = &replay.inner.header;
+
+        for player in &query.players {
+            if has_spy(&replay.inner, player) {
+                players.insert(
+                    name_key(&header.spy_user_name),
+                    header.spy_user_name.clone(),
+                );
+            }
+            if has_sniper(&replay.inner, player) {
+                players.insert(
+                    name_key(&header.sniper_user_name),
+                    header.sniper_user_name.clone(),
+                );
+            }
+        }
+    }
+
+    if players.len() > 1 {
+        let names: Vec<_> = players.values().map(String::as_str).collect();
+        bail!(
+            "'{}' matches more than one player ({}), so pass the name of the one to scout",
+            pattern,
+            names.join(", ")
+        );
+    }
+
+    Ok(players
+        .into_iter()
+        .next()
+        .map(|(_, player)| player)
+        .unwrap_or_else(|| pattern.to_string()))
+}
+
+/// Collects the stats of a single player into a report, with each section scoped to the role
+/// they played in each game.
+pub fn scout(replays: &[MatchedReplay], query: &Query, player: &str) -> Summary {
+    if replays.is_empty() {
+        return Summary {
+            total: 0,
+            sections: vec![],
+        };
+    }
+
+    let options = SummaryOptions::default();
+
+    let mut spy_record = RoleStats::spy(
+        PlayerStatCollection::default(),
+        player,
+        "spy_record",
+        "Record as Spy",
+    );
+    let mut sniper_record = RoleStats::sniper(
+        PlayerStatCollection::default(),
+        player,
+        "sniper_record",
+        "Record as Sniper",
+    );
+    let mut spy_maps = RoleStats::spy(
+        MapStatCollection::default(),
+        player,
+        "spy_maps",
+        "Maps Played as Spy",
+    );
+    let mut map_wins = MapWinStatCollection::default();
+    let mut spy_missions = RoleStats::spy(
+        MissionStatCollection::default(),
+        player,
+        "spy_missions_completed",
+        "Missions Completed as Spy",
+    );
+    let mut spy_uncompleted_missions = RoleStats::spy(
+        UncompletedMissionStatCollection::default(),
+        player,
+        "spy_missions_never_completed",
+        "Missions Picked but Never Completed as Spy",
+    );
+    let mut spy_mission_sets = RoleStats::spy(
+        MissionSetWinStatCollection::new(options.mission_sets),
+        player,
+        "spy_mission_set_win_rates",
+        "Mission Set Win Rates as Spy",
+    );
+    let mut spy_results = RoleStats::spy(
+        ResultStatCollection::default(),
+        player,
+        "spy_results",
+        "Results as Spy",
+    );
+    let mut sniper_results = RoleStats::sniper(
+        ResultStatCollection::default(),
+        player,
+        "sniper_results",
+        "Results as Sniper",
+    );
+    let mut spy_clock = RoleStats::spy(
+        ClockStatCollection::default(),
+        player,
+        "spy_clock",
+        "Clock as Spy",
+    );
+    let mut sniper_clock = RoleStats::sniper(
+        ClockStatCollection::default(),
+        player,
+        "sniper_clock",
+        "Clock as Sniper",
+    );
+
+    let mut all_stats: Vec<&mut dyn StatCollection> = vec![
+        &mut spy_record,
+        &mut sniper_record,
+        &mut spy_maps,
+        &mut map_wins,
+        &mut spy_missions,
+        &mut spy_uncompleted_missions,
+        &mut spy_mission_sets,
+        &mut spy_results,
+        &mut sniper_results,
+        &mut spy_clock,
+        &mut sniper_clock,
+    ];
+
+    for replay in replays {
+        for stats in &mut all_stats {
+            stats.aggregate(&replay.inner, query);
+        }
+    }
+
+    let mut sections: Vec<Section> = all_stats.iter().map(|stats| stats.section()).collect();
+    sections.push(form_section(replays, player));
+
+    Summary {
+        total: replays.len(),
+        sections,
+    }
+}
+
+/// Gets the recent form of a player as the spy, as the sniper and overall.
+fn form_section(replays: &[MatchedReplay], player: &str) -> Section {
+    let mut stats = Vec::new();
+
+    if let Some(form) = form::calculate(replays, &[player], DEFAULT_FORM_GAMES).pop() {
+        let roles = [
+            ("Spy", &form.spy),
+            ("Sniper", &form.sniper),
+            ("Overall", &form.overall),
+        ];
+
+        for (role, role_form) in &roles {
+            if let Some(recent_win_percentage) = role_form.recent_win_percentage() {
+                stats.push(FormStat {
+                    role: role.to_string(),
+                    wins: role_form.wins,
+                    losses: role_form.losses,
+                    recent_games: role_form.recent_games(),
+                    recent_win_percentage,
+                    current_streak: role_form.current_streak,
+                });
+            }
+        }
+    }
+
+    Section {
+        key: "form",
+        title: "Recent Form",
+        stats: SectionStats::Form(stats),
+    }
+}
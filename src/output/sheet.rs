@@ -0,0 +1,458 @@
+use crate::errors::*;
+use crate::matches;
+use crate::query::Query;
+use crate::utils::{clock_usage, has_sniper, has_spy, mode_to_string, start_time};
+use crate::MatchedReplay;
+use serde::Deserialize;
+use spyparty::{GameMode, Replay};
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+
+/// The most missions a game can have, which is how many columns the missions column expands into.
+const MAX_MISSIONS: usize = 8;
+
+/// The layout of a spreadsheet export: which tables are output, the columns of each one and the
+/// words used for wins, losses and whether the missions were completed.
+///
+/// Templates are usually loaded from a TOML file, where any setting that is left out keeps the
+/// default layout:
+///
+/// ```toml
+/// win = "Win"
+/// loss = "Loss"
+///
+/// [[tables]]
+/// role = "spy"
+/// columns = ["match", "map", { column = "result", label = "RESULT" }, "opponent"]
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Template {
+    /// The word for a game the player won.
+    pub win: String,
+    /// The word for a game the player lost.
+    pub loss: String,
+    /// The word for a game where enough missions were completed to win.
+    pub yes: String,
+    /// The word for a game where too few missions were completed to win.
+    pub no: String,
+    /// The tables to output, in order.
+    pub tables: Vec<TableTemplate>,
+}
+
+/// A single table in a spreadsheet export.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TableTemplate {
+    /// The games of the player which are included in the table.
+    pub role: Role,
+    /// The columns of the table, in order.
+    pub columns: Vec<ColumnTemplate>,
+}
+
+/// Which of the player's games are included in a table.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Spy,
+    Sniper,
+    Both,
+}
+
+/// A column in a table, with the label shown in its header.
+///
+/// In a template, a column is either just its name or a table with a name and a label, e.g.
+/// `{ column = "match", label = "GAME #" }`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "RawColumn")]
+pub struct ColumnTemplate {
+    pub column: Column,
+    pub label: Option<String>,
+}
+
+/// A column as it is written in a template, before its name is parsed.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawColumn {
+    Name(String),
+    Labelled {
+        column: String,
+        label: Option<String>,
+    },
+}
+
+/// Something about a game that can be shown in a column.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Column {
+    /// The number of the match the game was part of.
+    Match,
+    /// The number of the game within its match.
+    Game,
+    /// The date the game started on.
+    Date,
+    Map,
+    Mode,
+    /// Whether the player won or lost.
+    Result,
+    /// Whether enough missions were completed to win, whoever won.
+    MissionsComplete,
+    /// How the game ended.
+    Outcome,
+    /// The role the player played.
+    Role,
+    /// The other player.
+    Opponent,
+    /// The fraction of the starting clock that was used.
+    ClockUsage,
+    /// The length of the game in seconds.
+    Duration,
+    /// The completed missions, spread over a column for each.
+    Missions,
+    /// An empty column, to be filled in by hand.
+    Blank,
+}
+
+/// The header and rows of a table in a spreadsheet export.
+#[derive(Debug, PartialEq)]
+pub struct Sheet {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Default for Template {
+    /// The layout of the original export: a table of the player's games as the sniper, one of
+    /// their games as the spy and one of the missions they completed as the spy.
+    fn default() -> Self {
+        Template {
+            win: "W".to_string(),
+            loss: "L".to_string(),
+            yes: "Y".to_string(),
+            no: "N".to_string(),
+            tables: vec![
+                TableTemplate::new(
+                    Role::Sniper,
+                    &[
+                        (Column::Match, Some("SNIPER")),
+                        (Column::Map, None),
+                        (Column::Result, None),
+                        (Column::MissionsComplete, None),
+                        (Column::Outcome, None),
+                        (Column::Blank, Some("CHARACTER")),
+                        (Column::Blank, Some("CIVILIAN SHOT")),
+                        (Column::Opponent, None),
+                    ],
+                ),
+                TableTemplate::new(
+                    Role::Spy,
+                    &[
+                        (Column::Match, Some("SPY")),
+                        (Column::Map, None),
+                        (Column::Blank, Some("CHARACTER")),
+                        (Column::Blank, Some("AMBA")),
+                        (Column::Result, None),
+                        (Column::MissionsComplete, None),
+                        (Column::Outcome, None),
+                        (Column::Opponent, None),
+                        (Column::ClockUsage, None),
+                    ],
+                ),
+                TableTemplate::new(Role::Spy, &[(Column::Map, None), (Column::Missions, None)]),
+            ],
+        }
+    }
+}
+
+impl Template {
+    /// Loads a template from a TOML file.
+    pub fn load(path: &Path) -> Result<Template> {
+        let contents = fs::read_to_string(path)
+            .chain_err(|| format!("failed to read sheet template '{}'", path.display()))?;
+
+        Template::parse(&contents)
+            .chain_err(|| format!("failed to load sheet template '{}'", path.display()))
+    }
+
+    /// Parses a template from the contents of a TOML file.
+    pub fn parse(contents: &str) -> Result<Template> {
+        let template: Template = toml::from_str(contents).chain_err(|| "invalid sheet template")?;
+
+        if template.tables.iter().any(|table| table.columns.is_empty()) {
+            bail!("every table in a sheet template needs at least one column");
+        }
+
+        Ok(template)
+    }
+}
+
+impl TableTemplate {
+    /// Creates a table from a list of columns and their labels.
+    fn new(role: Role, columns: &[(Column, Option<&str>)]) -> Self {
+        TableTemplate {
+            role,
+            columns: columns
+                .iter()
+                .map(|&(column, label)| ColumnTemplate {
+                    column,
+                    label: label.map(String::from),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<RawColumn> for ColumnTemplate {
+    type Error = Error;
+
+    fn try_from(raw: RawColumn) -> Result<Self> {
+        let (name, label) = match raw {
+            RawColumn::Name(name) => (name, None),
+            RawColumn::Labelled { column, label } => (column, label),
+        };
+
+        Ok(ColumnTemplate {
+            column: Column::parse(&name)?,
+            label,
+        })
+    }
+}
+
+impl Column {
+    /// The names of every column, as they are written in a template.
+    const NAMES: [&'static str; 14] = [
+        "match",
+        "game",
+        "date",
+        "map",
+        "mode",
+        "result",
+        "missions-complete",
+        "outcome",
+        "role",
+        "opponent",
+        "clock-usage",
+        "duration",
+        "missions",
+        "blank",
+    ];
+
+    /// Parses the name of a column.
+    pub fn parse(value: &str) -> Result<Column> {
+        let column = match value.to_ascii_lowercase().as_str() {
+            "match" => Column::Match,
+            "game" => Column::Game,
+            "date" => Column::Date,
+            "map" => Column::Map,
+            "mode" => Column::Mode,
+            "result" => Column::Result,
+            "missions-complete" => Column::MissionsComplete,
+            "outcome" => Column::Outcome,
+            "role" => Column::Role,
+            "opponent" => Column::Opponent,
+            "clock-usage" => Column::ClockUsage,
+            "duration" => Column::Duration,
+            "missions" => Column::Missions,
+            "blank" => Column::Blank,
+            _ => bail!(
+                "'{}' is not a valid column (expected one of {})",
+                value,
+                Column::NAMES.join(", ")
+            ),
+        };
+
+        Ok(column)
+    }
+
+    /// The default label of the column in the header.
+    fn label(self) -> &'static str {
+        match self {
+            Column::Match => "MATCH",
+            Column::Game => "GAME",
+            Column::Date => "DATE",
+            Column::Map => "MAP",
+            Column::Mode => "MODE",
+            Column::Result => "W/L",
+            Column::MissionsComplete => "MISSION COMPLETE",
+            Column::Outcome => "OUTCOME",
+            Column::Role => "ROLE",
+            Column::Opponent => "OPPONENT",
+            Column::ClockUsage => "CLOCK USAGE",
+            Column::Duration => "DURATION",
+            Column::Missions => "MISSION",
+            Column::Blank => "",
+        }
+    }
+}
+
+/// Outputs the tables of a spreadsheet export as CSV, separated by blank lines.
+///
+/// Every table is from the point of view of the first player passed with --players or --pair.
+pub fn show(replays: &[MatchedReplay], query: &Query, template: &Template) -> Result<()> {
+    let player = match query.match_player() {
+        Some(player) => player,
+        None => bail!("the sheet export needs a player, passed with --players or --pair"),
+    };
+
+    for (i, sheet) in build(replays, player, template).iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+
+        let mut wtr = csv::Writer::from_writer(std::io::stdout());
+
+        wtr.write_record(&sheet.header)
+            .chain_err(|| "failed to write CSV record")?;
+        for row in &sheet.rows {
+            wtr.write_record(row)
+                .chain_err(|| "failed to write CSV record")?;
+        }
+
+        wtr.flush().chain_err(|| "failed to write CSV")?;
+    }
+
+    Ok(())
+}
+
+/// Builds each table of a template from the games of a player, numbering matches in the order
+/// they were played.
+pub fn build(replays: &[MatchedReplay], player: &str, template: &Template) -> Vec<Sheet> {
+    let game_matches = matches::group(replays);
+
+    template
+        .tables
+        .iter()
+        .map(|table| {
+            let mut rows = Vec::new();
+
+            for (sequence, game_match) in game_matches.iter().enumerate() {
+                for replay in &game_match.games {
+                    if let Some(as_spy) = player_role(&replay.inner, player, table.role) {
+                        let game = Game {
+                            replay: &replay.inner,
+                            sequence: sequence + 1,
+                            as_spy,
+                        };
+
+                        rows.push(game.row(table, template));
+                    }
+                }
+            }
+
+            Sheet {
+                header: header(table),
+                rows,
+            }
+        })
+        .collect()
+}
+
+/// Gets whether the player was the spy in a game included in a table, or `None` if the game
+/// isn't included. Games a player played against themselves count as games as the sniper.
+fn player_role(replay: &Replay, player: &str, role: Role) -> Option<bool> {
+    let as_sniper = has_sniper(replay, player);
+    let as_spy = !as_sniper && has_spy(replay, player);
+
+    match role {
+        Role::Spy if as_spy => Some(true),
+        Role::Sniper if as_sniper => Some(false),
+        Role::Both if as_spy || as_sniper => Some(as_spy),
+        _ => None,
+    }
+}
+
+/// Creates the header of a table.
+fn header(table: &TableTemplate) -> Vec<String> {
+    let mut header = Vec::new();
+
+    for ColumnTemplate { column, label } in &table.columns {
+        let label = label.as_deref().unwrap_or_else(|| column.label());
+
+        if *column == Column::Missions {
+            header.extend((1..=MAX_MISSIONS).map(|i| format!("{} #{}", label, i)));
+        } else {
+            header.push(label.to_string());
+        }
+    }
+
+    header
+}
+
+/// A game in a table, along with the number of its match and the role the player played.
+struct Game<'a> {
+    replay: &'a Replay,
+    sequence: usize,
+    as_spy: bool,
+}
+
+impl<'a> Game<'a> {
+    /// Creates the row of the game in a table.
+    fn row(&self, table: &TableTemplate, template: &Template) -> Vec<String> {
+        let mut row = Vec::new();
+
+        for ColumnTemplate { column, .. } in &table.columns {
+            if *column == Column::Missions {
+                let missions = &self.replay.header.result_data.completed_missions;
+
+                row.extend(missions.iter().map(ToString::to_string));
+                row.extend((missions.len()..MAX_MISSIONS).map(|_| String::new()));
+            } else {
+                row.push(self.value(*column, template));
+            }
+        }
+
+        row
+    }
+
+    /// Gets the value of a single cell.
+    fn value(&self, column: Column, template: &Template) -> String {
+        let header = &self.replay.header;
+        let result_data = &header.result_data;
+
+        match column {
+            Column::Match => self.sequence.to_string(),
+            Column::Game => header.play_id.to_string(),
+            Column::Date => start_time(self.replay).format("%Y-%m-%d").to_string(),
+            Column::Map => result_data.map.to_string(),
+            Column::Mode => mode_to_string(&result_data.game_mode),
+            Column::Result => {
+                let (won, lost) = if self.as_spy {
+                    (self.replay.is_spy_win(), self.replay.is_sniper_win())
+                } else {
+                    (self.replay.is_sniper_win(), self.replay.is_spy_win())
+                };
+
+                if won {
+                    template.win.clone()
+                } else if lost {
+                    template.loss.clone()
+                } else {
+                    String::new()
+                }
+            }
+            Column::MissionsComplete => {
+                let required = match result_data.game_mode {
+                    GameMode::Known(required) => required,
+                    GameMode::Pick(required, _) => required,
+                    GameMode::Any(required, _) => required,
+                };
+
+                if result_data.completed_missions.len() >= required as usize {
+                    template.yes.clone()
+                } else {
+                    template.no.clone()
+                }
+            }
+            Column::Outcome => format!("{:?}", result_data.game_result),
+            Column::Role if self.as_spy => "Spy".to_string(),
+            Column::Role => "Sniper".to_string(),
+            Column::Opponent if self.as_spy => self.replay.sniper_name().clone(),
+            Column::Opponent => self.replay.spy_name().clone(),
+            Column::ClockUsage => match clock_usage(self.replay) {
+                Some(clock_usage) => format!("{:.2}", clock_usage),
+                None => String::new(),
+            },
+            Column::Duration => format!("{:.0}", header.duration),
+            Column::Missions | Column::Blank => String::new(),
+        }
+    }
+}
@@ -1,59 +1,277 @@
-/// Macro used by a StatCollection which only focuses on one value (like map count) to print the
-/// results to stdout.
-macro_rules! print_single {
-    ($self:ident, $title:expr) => {
+/// Macro used by a StatCollection which only focuses on one value (like map count) to create a
+/// section of the summary.
+macro_rules! single_section {
+    ($self:ident, $key:expr, $title:expr) => {{
         let mut collection: Vec<_> = $self.stats.iter().collect();
         collection.sort_by(|(a_name, a_sum), (b_name, b_sum)| {
             b_sum.cmp(&a_sum).then(a_name.cmp(&b_name))
         });
 
-        println!("{}:", $title);
-        for (name, value) in collection {
-            println!(
-                "    {}: {} ({:.1}%)",
-                name,
-                value,
-                (*value as f32 / $self.total as f32) * 100f32
-            );
+        let stats = collection
+            .into_iter()
+            .map(|(name, value)| CountStat {
+                name: name.to_string(),
+                count: *value,
+                percentage: (*value as f32 / $self.total as f32) * 100f32,
+            })
+            .collect();
+
+        Section {
+            key: $key,
+            title: $title,
+            stats: SectionStats::Counts(stats),
         }
-    };
+    }};
 }
 
 mod clock_stat_collection;
 mod map_stat_collection;
+mod map_win_stat_collection;
+mod mission_breakdown_stat_collection;
 mod mission_set_stat_collection;
+mod mission_set_win_stat_collection;
 mod mission_stat_collection;
 mod mode_stat_collection;
 mod player_stat_collection;
+mod render;
 mod result_stat_collection;
+mod uncompleted_mission_stat_collection;
+
+pub use clock_stat_collection::ClockStatCollection;
+pub use map_stat_collection::MapStatCollection;
+pub use map_win_stat_collection::MapWinStatCollection;
+pub use mission_breakdown_stat_collection::MissionBreakdownStatCollection;
+pub use mission_set_stat_collection::MissionSetStatCollection;
+pub use mission_set_win_stat_collection::MissionSetWinStatCollection;
+pub use mission_stat_collection::MissionStatCollection;
+pub use mode_stat_collection::ModeStatCollection;
+pub use player_stat_collection::PlayerStatCollection;
+pub use result_stat_collection::ResultStatCollection;
+pub use uncompleted_mission_stat_collection::UncompletedMissionStatCollection;
+
+pub(crate) use map_stat_collection::map_name;
+pub(crate) use result_stat_collection::result_name;
 
+use crate::errors::*;
+use crate::query::{ModeFilter, Query};
+use crate::utils::{has_sniper, has_spy, sniper_matches, spy_matches};
 use crate::MatchedReplay;
-use clap::ArgMatches;
-use clock_stat_collection::ClockStatCollection;
-use map_stat_collection::MapStatCollection;
-use mission_set_stat_collection::MissionSetStatCollection;
-use mission_stat_collection::MissionStatCollection;
-use mode_stat_collection::ModeStatCollection;
-use player_stat_collection::PlayerStatCollection;
-use result_stat_collection::ResultStatCollection;
+use serde::ser::{Serialize, SerializeMap, Serializer};
 use spyparty::Replay;
 use std::collections::HashMap;
 
 /// A trait defining a collection of stats.
-trait StatCollection {
+pub trait StatCollection {
     /// Adds the stats of a replay to the collection.
-    fn aggregate(&mut self, replay: &Replay, matches: &ArgMatches);
-    /// Prints the stats to stdout.
-    fn print(&self);
+    fn aggregate(&mut self, replay: &Replay, query: &Query);
+    /// Gets the stats as a section of the summary.
+    fn section(&self) -> Section;
+}
+
+/// The different formats a summary can be shown in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SummaryFormat {
+    /// Plain text, meant to be read in a terminal.
+    Text,
+    /// A JSON object.
+    Json,
+    /// A YAML document.
+    Yaml,
+    /// Markdown tables.
+    Markdown,
+}
+
+/// Options which change how much of each section of a summary is shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SummaryOptions {
+    /// The number of mission sets shown.
+    pub mission_sets: usize,
+    /// Whether to also show the breakdowns of missions by map and mode, the missions which were
+    /// never completed and the win rates of mission sets.
+    pub detailed: bool,
+}
+
+impl Default for SummaryOptions {
+    fn default() -> Self {
+        SummaryOptions {
+            mission_sets: 10,
+            detailed: false,
+        }
+    }
+}
+
+/// A summary of stats about a set of replays.
+pub struct Summary {
+    /// The number of replays in the summary.
+    pub total: usize,
+    /// The stats of each collection, in the order they should be shown.
+    pub sections: Vec<Section>,
+}
+
+/// The stats of a single collection.
+pub struct Section {
+    /// A short name for the section, used as a key in structured formats.
+    pub key: &'static str,
+    /// A readable title for the section.
+    pub title: &'static str,
+    /// The stats in the section.
+    pub stats: SectionStats,
+}
+
+/// The different kinds of stats a collection can produce.
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+pub enum SectionStats {
+    /// How many times each thing happened, out of some total.
+    Counts(Vec<CountStat>),
+    /// The wins and losses of players.
+    Players(Vec<PlayerStat>),
+    /// Game lengths and clock usage.
+    Clock(ClockStat),
+    /// Who won on each map.
+    MapWins(Vec<MapWinStat>),
+    /// Counts split into groups of games (like the games on each map).
+    Groups(Vec<GroupStat>),
+    /// How often the spy won after completing each set of missions.
+    MissionSets(Vec<MissionSetWinStat>),
+    /// The recent form of a player in each role.
+    Form(Vec<FormStat>),
+}
+
+/// How many times a single thing (like a map) happened.
+#[derive(serde::Serialize)]
+pub struct CountStat {
+    pub name: String,
+    pub count: u32,
+    pub percentage: f32,
+}
+
+/// The wins and losses of a single player.
+#[derive(serde::Serialize)]
+pub struct PlayerStat {
+    pub user_name: String,
+    pub display_name: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub win_percentage: f32,
+}
+
+/// The wins and results on a single map.
+#[derive(serde::Serialize)]
+pub struct MapWinStat {
+    pub map: String,
+    pub games: u32,
+    pub spy_wins: u32,
+    pub sniper_wins: u32,
+    pub spy_win_percentage: f32,
+    pub sniper_win_percentage: f32,
+    /// How many games on the map ended in each result.
+    pub results: Vec<CountStat>,
+    /// The wins and losses on the map of each player in the player filters, if any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub players: Vec<PlayerMapWinStat>,
+}
+
+/// The wins and losses of a single player on a map, in each role.
+#[derive(Clone, Default, serde::Serialize)]
+pub struct PlayerMapWinStat {
+    pub user_name: String,
+    pub display_name: String,
+    pub spy_wins: u32,
+    pub spy_losses: u32,
+    pub sniper_wins: u32,
+    pub sniper_losses: u32,
+}
+
+/// Counts within a group of games, such as how often each mission was completed on a map.
+#[derive(serde::Serialize)]
+pub struct GroupStat {
+    pub name: String,
+    pub games: u32,
+    pub stats: Vec<CountStat>,
+}
+
+/// How often the spy won or was shot after completing a single set of missions.
+#[derive(serde::Serialize)]
+pub struct MissionSetWinStat {
+    pub missions: String,
+    pub games: u32,
+    pub spy_wins: u32,
+    pub spy_shots: u32,
+    pub spy_win_percentage: f32,
+}
+
+/// The recent results of a player in a single role.
+#[derive(serde::Serialize)]
+pub struct FormStat {
+    pub role: String,
+    pub wins: u32,
+    pub losses: u32,
+    /// The number of recent games the win percentage is based on.
+    pub recent_games: usize,
+    pub recent_win_percentage: f32,
+    /// The streak the player is currently on, which is positive for wins and negative for losses.
+    pub current_streak: i32,
+}
+
+/// The average length of games and how much of the clock was used.
+#[derive(serde::Serialize)]
+pub struct ClockStat {
+    /// The average duration of a game, in seconds.
+    pub average_duration: f32,
+    /// The percentage of the starting clock used, if any starting clocks were known.
+    pub clock_usage_percentage: Option<f32>,
+}
+
+impl Serialize for Summary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.sections.len() + 1))?;
+
+        map.serialize_entry("total_replays", &self.total)?;
+        for section in &self.sections {
+            map.serialize_entry(section.key, &section.stats)?;
+        }
+
+        map.end()
+    }
 }
 
 /// Shows a summary of the filtered replays.
 ///
 /// What is shown is based on which filters were used when querying the replays.
-pub fn show(replays: &[MatchedReplay], matches: &ArgMatches) {
+pub fn show(
+    replays: &[MatchedReplay],
+    query: &Query,
+    format: SummaryFormat,
+    options: SummaryOptions,
+) -> Result<()> {
+    render(&summarize(replays, query, options), format)
+}
+
+/// Prints a summary in a particular format.
+pub(crate) fn render(summary: &Summary, format: SummaryFormat) -> Result<()> {
+    match format {
+        SummaryFormat::Text => render::text(summary),
+        SummaryFormat::Json => render::json(summary)?,
+        SummaryFormat::Yaml => render::yaml(summary)?,
+        SummaryFormat::Markdown => render::markdown(summary),
+    }
+
+    Ok(())
+}
+
+/// Collects the stats of the filtered replays into a summary.
+///
+/// Which stats are collected is based on which filters were used when querying the replays.
+pub fn summarize(replays: &[MatchedReplay], query: &Query, options: SummaryOptions) -> Summary {
     let mut map_stats: MapStatCollection = Default::default();
+    let mut map_win_stats: MapWinStatCollection = Default::default();
     let mut mission_stats: MissionStatCollection = Default::default();
-    let mut mission_set_stats: MissionSetStatCollection = Default::default();
+    let mut mission_map_stats = MissionBreakdownStatCollection::by_map();
+    let mut mission_mode_stats = MissionBreakdownStatCollection::by_mode();
+    let mut uncompleted_mission_stats: UncompletedMissionStatCollection = Default::default();
+    let mut mission_set_stats = MissionSetStatCollection::new(options.mission_sets);
+    let mut mission_set_win_stats = MissionSetWinStatCollection::new(options.mission_sets);
     let mut mode_stats: ModeStatCollection = Default::default();
     let mut result_stats: ResultStatCollection = Default::default();
     let mut player_stats: PlayerStatCollection = Default::default();
@@ -63,52 +281,124 @@ pub fn show(replays: &[MatchedReplay], matches: &ArgMatches) {
 
     // Early return if no replays!
     if replays.is_empty() {
-        println!("No replays found.");
-        return;
+        return Summary {
+            total: 0,
+            sections: vec![],
+        };
     }
 
     // Show player stats if filtering on players
-    if matches.is_present("pair")
-        || matches.is_present("players")
-        || matches.is_present("spies")
-        || matches.is_present("snipers")
-    {
+    if query.has_player_filters() {
         all_stats.push(&mut player_stats);
     }
 
     // Show map stats if not filtered by maps
-    if !matches.is_present("maps") {
+    if query.maps.is_empty() {
         all_stats.push(&mut map_stats);
     }
 
+    // Show map win rates unless filtered to a single map
+    if query.maps.len() != 1 {
+        all_stats.push(&mut map_win_stats);
+    }
+
     // Always show missions stats
     all_stats.push(&mut mission_stats);
+    if options.detailed {
+        all_stats.push(&mut uncompleted_mission_stats);
+
+        // Break missions down by map and mode unless filtered to a single one
+        if query.maps.len() != 1 {
+            all_stats.push(&mut mission_map_stats);
+        }
+        match query.modes.as_slice() {
+            [ModeFilter::Exact(_)] => {}
+            _ => all_stats.push(&mut mission_mode_stats),
+        }
+    }
+
     all_stats.push(&mut mission_set_stats);
+    if options.detailed {
+        all_stats.push(&mut mission_set_win_stats);
+    }
 
     // Always show clock stats
     all_stats.push(&mut clock_stats);
 
     // Show mode stats if not filtered by modes
-    if !matches.is_present("modes") {
+    if query.modes.is_empty() {
         all_stats.push(&mut mode_stats);
     }
 
     // Show result stats if not filtered by results
-    if !matches.is_present("results") {
+    if query.results.is_empty() {
         all_stats.push(&mut result_stats);
     }
 
     for replay in replays {
         for stats in &mut all_stats {
-            stats.aggregate(&replay.inner, matches);
+            stats.aggregate(&replay.inner, query);
         }
     }
 
-    println!("Total Replays:\n    {}", replays.len());
+    Summary {
+        total: replays.len(),
+        sections: all_stats.iter().map(|stats| stats.section()).collect(),
+    }
+}
 
-    for stats in all_stats {
-        stats.print();
+/// Gets the names of the players in the player filters of a query, along with whether their
+/// games as the spy and as the sniper should be counted. Players matched by a regular expression
+/// are counted in both roles.
+fn query_players(query: &Query) -> (Vec<&str>, bool, bool) {
+    let mut players: Vec<&str> = Vec::new();
+    let mut count_spy = false;
+    let mut count_sniper = false;
+
+    if !query.pair.is_empty() {
+        count_spy = true;
+        count_sniper = true;
+        players.extend(query.pair.iter().map(String::as_str));
+    } else if !query.players.is_empty() {
+        count_spy = true;
+        count_sniper = true;
+        players.extend(query.players.iter().map(String::as_str));
+    }
+
+    if !query.spies.is_empty() {
+        count_spy = true;
+        players.extend(query.spies.iter().map(String::as_str));
     }
+
+    if !query.snipers.is_empty() {
+        count_sniper = true;
+        players.extend(query.snipers.iter().map(String::as_str));
+    }
+
+    if !query.players_regex.is_empty() {
+        count_spy = true;
+        count_sniper = true;
+    }
+
+    (players, count_spy, count_sniper)
+}
+
+/// Checks if the spy of a replay is one of the players in the player filters of a query.
+fn is_query_spy(replay: &Replay, query: &Query, players: &[&str]) -> bool {
+    players.iter().any(|player| has_spy(replay, player))
+        || query
+            .players_regex
+            .iter()
+            .any(|regex| spy_matches(replay, regex))
+}
+
+/// Checks if the sniper of a replay is one of the players in the player filters of a query.
+fn is_query_sniper(replay: &Replay, query: &Query, players: &[&str]) -> bool {
+    players.iter().any(|player| has_sniper(replay, player))
+        || query
+            .players_regex
+            .iter()
+            .any(|regex| sniper_matches(replay, regex))
 }
 
 /// A helper function which increments a key in a hashmap or initializes it to 1 if it doesn't
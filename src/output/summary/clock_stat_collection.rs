@@ -1,5 +1,5 @@
-use super::StatCollection;
-use clap::ArgMatches;
+use super::{ClockStat, Section, SectionStats, StatCollection};
+use crate::query::Query;
 use spyparty::Replay;
 
 /// A collection for keeping track lengths of games and clock usage.
@@ -12,7 +12,7 @@ pub struct ClockStatCollection {
 }
 
 impl StatCollection for ClockStatCollection {
-    fn aggregate(&mut self, replay: &Replay, _: &ArgMatches) {
+    fn aggregate(&mut self, replay: &Replay, _: &Query) {
         let duration = replay.header.duration;
 
         if let Some(clock_start) = replay.header.result_data.clock_start {
@@ -24,22 +24,20 @@ impl StatCollection for ClockStatCollection {
         self.total += 1;
     }
 
-    fn print(&self) {
-        println!("Clock:");
-
-        let average_duration = (self.total_duration / self.total as f32).round() as u32;
-        let minutes = average_duration / 60;
-        let seconds = average_duration % 60;
-
-        println!("    Average Duration: {}m{}s", minutes, seconds);
-
-        if self.total_clock > 0 {
-            println!(
-                "    Clock Usage: {:.1}%",
-                (self.total_duration_with_clock / self.total_clock as f32) * 100f32
-            );
+    fn section(&self) -> Section {
+        let clock_usage_percentage = if self.total_clock > 0 {
+            Some((self.total_duration_with_clock / self.total_clock as f32) * 100f32)
         } else {
-            println!("    Clock Usage: Unknown");
+            None
+        };
+
+        Section {
+            key: "clock",
+            title: "Clock",
+            stats: SectionStats::Clock(ClockStat {
+                average_duration: self.total_duration / self.total as f32,
+                clock_usage_percentage,
+            }),
         }
     }
 }
@@ -1,5 +1,5 @@
-use super::{increment, StatCollection};
-use clap::ArgMatches;
+use super::{increment, CountStat, Section, SectionStats, StatCollection};
+use crate::query::Query;
 use spyparty::{Map, Replay};
 use std::collections::HashMap;
 
@@ -11,38 +11,43 @@ pub struct MapStatCollection {
 }
 
 impl StatCollection for MapStatCollection {
-    fn aggregate(&mut self, replay: &Replay, _: &ArgMatches) {
-        match replay.header.result_data.map {
-            Map::Aquarium => increment(&mut self.stats, "Aquarium"),
-            Map::Balcony => increment(&mut self.stats, "Balcony"),
-            Map::Ballroom => increment(&mut self.stats, "Ballroom"),
-            Map::Courtyard => increment(&mut self.stats, "Courtyard"),
-            Map::CrowdedPub => increment(&mut self.stats, "Crowded Pub"),
-            Map::DoubleModern => increment(&mut self.stats, "Double Modern"),
-            Map::Gallery => increment(&mut self.stats, "Gallery"),
-            Map::HighRise => increment(&mut self.stats, "High-Rise"),
-            Map::Library => increment(&mut self.stats, "Library"),
-            Map::Modern => increment(&mut self.stats, "Modern"),
-            Map::Moderne => increment(&mut self.stats, "Moderne"),
-            Map::OldBalcony => increment(&mut self.stats, "Old Balcony"),
-            Map::OldBallroom => increment(&mut self.stats, "Old Ballroom"),
-            Map::OldCourtyard1 => increment(&mut self.stats, "Old Courtyard 1"),
-            Map::OldCourtyard2 => increment(&mut self.stats, "Old Courtyard 2"),
-            Map::OldGallery => increment(&mut self.stats, "Old Gallery"),
-            Map::OldVeranda => increment(&mut self.stats, "Old Veranda"),
-            Map::Panopticon => increment(&mut self.stats, "Panopticon"),
-            Map::Pub => increment(&mut self.stats, "Pub"),
-            Map::Redwoods => increment(&mut self.stats, "Redwoods"),
-            Map::Teien => increment(&mut self.stats, "Teien"),
-            Map::Terrace => increment(&mut self.stats, "Terrace"),
-            Map::Veranda => increment(&mut self.stats, "Veranda"),
-            _ => increment(&mut self.stats, "Unknown"),
-        }
+    fn aggregate(&mut self, replay: &Replay, _: &Query) {
+        increment(&mut self.stats, map_name(&replay.header.result_data.map));
 
         self.total += 1;
     }
 
-    fn print(&self) {
-        print_single!(self, "Maps Played");
+    fn section(&self) -> Section {
+        single_section!(self, "maps", "Maps Played")
+    }
+}
+
+/// Gets the readable name of a map.
+pub(crate) fn map_name(map: &Map) -> &'static str {
+    match map {
+        Map::Aquarium => "Aquarium",
+        Map::Balcony => "Balcony",
+        Map::Ballroom => "Ballroom",
+        Map::Courtyard => "Courtyard",
+        Map::CrowdedPub => "Crowded Pub",
+        Map::DoubleModern => "Double Modern",
+        Map::Gallery => "Gallery",
+        Map::HighRise => "High-Rise",
+        Map::Library => "Library",
+        Map::Modern => "Modern",
+        Map::Moderne => "Moderne",
+        Map::OldBalcony => "Old Balcony",
+        Map::OldBallroom => "Old Ballroom",
+        Map::OldCourtyard1 => "Old Courtyard 1",
+        Map::OldCourtyard2 => "Old Courtyard 2",
+        Map::OldGallery => "Old Gallery",
+        Map::OldVeranda => "Old Veranda",
+        Map::Panopticon => "Panopticon",
+        Map::Pub => "Pub",
+        Map::Redwoods => "Redwoods",
+        Map::Teien => "Teien",
+        Map::Terrace => "Terrace",
+        Map::Veranda => "Veranda",
+        _ => "Unknown",
     }
 }
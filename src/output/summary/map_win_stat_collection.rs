@@ -0,0 +1,130 @@
+use super::map_stat_collection::map_name;
+use super::result_stat_collection::result_name;
+use super::{
+    increment, is_query_sniper, is_query_spy, query_players, CountStat, MapWinStat,
+    PlayerMapWinStat, Section, SectionStats, StatCollection,
+};
+use crate::query::Query;
+use crate::utils::percentage;
+use spyparty::Replay;
+use std::collections::HashMap;
+
+/// A collection for keeping track of who won on each map, and how.
+#[derive(Default)]
+pub struct MapWinStatCollection {
+    stats: HashMap<&'static str, MapWins>,
+}
+
+/// The wins on a single map.
+#[derive(Default)]
+struct MapWins {
+    games: u32,
+    spy_wins: u32,
+    sniper_wins: u32,
+    results: HashMap<&'static str, u32>,
+    players: HashMap<String, PlayerMapWinStat>,
+}
+
+impl StatCollection for MapWinStatCollection {
+    fn aggregate(&mut self, replay: &Replay, query: &Query) {
+        let result_data = &replay.header.result_data;
+        let map_wins = self.stats.entry(map_name(&result_data.map)).or_default();
+
+        map_wins.games += 1;
+        if replay.is_spy_win() {
+            map_wins.spy_wins += 1;
+        } else if replay.is_sniper_win() {
+            map_wins.sniper_wins += 1;
+        }
+        increment(&mut map_wins.results, result_name(&result_data.game_result));
+
+        // Split the wins by player if filtering on players
+        let (players, count_spy_wins, count_sniper_wins) = query_players(query);
+
+        if count_spy_wins && is_query_spy(replay, query, &players) {
+            let stat = player_stat(
+                &mut map_wins.players,
+                &replay.header.spy_user_name,
+                replay.spy_name(),
+            );
+
+            if replay.is_spy_win() {
+                stat.spy_wins += 1;
+            } else if replay.is_sniper_win() {
+                stat.spy_losses += 1;
+            }
+        }
+
+        if count_sniper_wins && is_query_sniper(replay, query, &players) {
+            let stat = player_stat(
+                &mut map_wins.players,
+                &replay.header.sniper_user_name,
+                replay.sniper_name(),
+            );
+
+            if replay.is_sniper_win() {
+                stat.sniper_wins += 1;
+            } else if replay.is_spy_win() {
+                stat.sniper_losses += 1;
+            }
+        }
+    }
+
+    fn section(&self) -> Section {
+        let mut collection: Vec<_> = self.stats.iter().collect();
+        collection.sort_by(|(a_name, a_wins), (b_name, b_wins)| {
+            b_wins.games.cmp(&a_wins.games).then(a_name.cmp(b_name))
+        });
+
+        let stats = collection
+            .into_iter()
+            .map(|(name, wins)| {
+                let mut results: Vec<_> = wins
+                    .results
+                    .iter()
+                    .map(|(result, count)| CountStat {
+                        name: result.to_string(),
+                        count: *count,
+                        percentage: percentage(*count, wins.games),
+                    })
+                    .collect();
+                results.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+
+                let mut players: Vec<_> = wins.players.values().cloned().collect();
+                players.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+
+                MapWinStat {
+                    map: name.to_string(),
+                    games: wins.games,
+                    spy_wins: wins.spy_wins,
+                    sniper_wins: wins.sniper_wins,
+                    spy_win_percentage: percentage(wins.spy_wins, wins.games),
+                    sniper_win_percentage: percentage(wins.sniper_wins, wins.games),
+                    results,
+                    players,
+                }
+            })
+            .collect();
+
+        Section {
+            key: "map_wins",
+            title: "Map Win Rates",
+            stats: SectionStats::MapWins(stats),
+        }
+    }
+}
+
+/// Gets the stats of a player on a map, creating them if they don't exist.
+fn player_stat<'a>(
+    players: &'a mut HashMap<String, PlayerMapWinStat>,
+    user_name: &str,
+    display_name: &str,
+) -> &'a mut PlayerMapWinStat {
+    players
+        .entry(user_name.to_string())
+        .or_insert_with(|| PlayerMapWinStat {
+            user_name: user_name.to_string(),
+            display_name: display_name.to_string(),
+            ..Default::default()
+        })
+}
@@ -0,0 +1,114 @@
+use super::map_stat_collection::map_name;
+use super::mission_stat_collection::increment_missions;
+use super::{CountStat, GroupStat, Section, SectionStats, StatCollection};
+use crate::query::Query;
+use crate::utils::{mode_to_string, percentage};
+use spyparty::Replay;
+use std::collections::HashMap;
+
+/// A collection for keeping track of how often each mission was completed, broken down by
+/// something about the game (like the map).
+pub struct MissionBreakdownStatCollection {
+    breakdown: Breakdown,
+    stats: HashMap<String, MissionCounts>,
+}
+
+/// The different things missions can be broken down by.
+#[derive(Clone, Copy)]
+enum Breakdown {
+    Map,
+    Mode,
+}
+
+/// How many times each mission was picked and completed in a group of games.
+#[derive(Default)]
+struct MissionCounts {
+    games: u32,
+    selected: HashMap<&'static str, u32>,
+    completed: HashMap<&'static str, u32>,
+}
+
+impl MissionBreakdownStatCollection {
+    /// Creates a collection which breaks missions down by map.
+    pub fn by_map() -> Self {
+        MissionBreakdownStatCollection {
+            breakdown: Breakdown::Map,
+            stats: HashMap::new(),
+        }
+    }
+
+    /// Creates a collection which breaks missions down by game mode.
+    pub fn by_mode() -> Self {
+        MissionBreakdownStatCollection {
+            breakdown: Breakdown::Mode,
+            stats: HashMap::new(),
+        }
+    }
+}
+
+impl StatCollection for MissionBreakdownStatCollection {
+    fn aggregate(&mut self, replay: &Replay, _: &Query) {
+        let result_data = &replay.header.result_data;
+        let group = match self.breakdown {
+            Breakdown::Map => map_name(&result_data.map).to_string(),
+            Breakdown::Mode => mode_to_string(&result_data.game_mode),
+        };
+        let counts = self.stats.entry(group).or_default();
+
+        counts.games += 1;
+        increment_missions(&mut counts.selected, &result_data.selected_missions);
+        increment_missions(&mut counts.completed, &result_data.completed_missions);
+    }
+
+    fn section(&self) -> Section {
+        let mut collection: Vec<_> = self.stats.iter().collect();
+        collection.sort_by(|(a_name, a_counts), (b_name, b_counts)| {
+            b_counts.games.cmp(&a_counts.games).then(a_name.cmp(b_name))
+        });
+
+        let stats = collection
+            .into_iter()
+            .map(|(name, counts)| {
+                // Include missions which were picked but never completed, so they show as 0%
+                let mut missions: Vec<_> = counts
+                    .selected
+                    .iter()
+                    .map(|(mission, selected)| {
+                        let completed = counts.completed.get(mission).copied().unwrap_or(0);
+
+                        CountStat {
+                            name: mission.to_string(),
+                            count: completed,
+                            percentage: percentage(completed, *selected),
+                        }
+                    })
+                    .collect();
+                missions.sort_by(|a, b| {
+                    b.percentage
+                        .partial_cmp(&a.percentage)
+                        .unwrap()
+                        .then(a.name.cmp(&b.name))
+                });
+
+                GroupStat {
+                    name: name.to_string(),
+                    games: counts.games,
+                    stats: missions,
+                }
+            })
+            .collect();
+
+        match self.breakdown {
+            Breakdown::Map => Section {
+                key: "missions_completed_by_map",
+                title: "Missions Completed by Map",
+                stats: SectionStats::Groups(stats),
+            },
+            Breakdown::Mode => Section {
+                key: "missions_completed_by_mode",
+                title: "Missions Completed by Mode",
+                stats: SectionStats::Groups(stats),
+            },
+        }
+    }
+}
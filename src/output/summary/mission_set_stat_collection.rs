@@ -1,17 +1,28 @@
-use super::StatCollection;
-use clap::ArgMatches;
+use super::{CountStat, Section, SectionStats, StatCollection};
+use crate::query::Query;
 use spyparty::{GameMode, Mission, Replay};
 use std::collections::HashMap;
 
 /// A collection for keeping track of how many times a set of missions counts as a completion.
-#[derive(Default)]
 pub struct MissionSetStatCollection {
     total: u32,
+    top: usize,
     stats: HashMap<u32, u32>,
 }
 
+impl MissionSetStatCollection {
+    /// Creates a collection which shows the `top` most completed mission sets.
+    pub fn new(top: usize) -> Self {
+        MissionSetStatCollection {
+            total: 0,
+            top,
+            stats: HashMap::new(),
+        }
+    }
+}
+
 impl StatCollection for MissionSetStatCollection {
-    fn aggregate(&mut self, replay: &Replay, _: &ArgMatches) {
+    fn aggregate(&mut self, replay: &Replay, _: &Query) {
         let required_missions = match replay.header.result_data.game_mode {
             GameMode::Any(x, ..) | GameMode::Pick(x, ..) | GameMode::Known(x) => x,
         };
@@ -27,25 +38,39 @@ impl StatCollection for MissionSetStatCollection {
         }
     }
 
-    fn print(&self) {
+    fn section(&self) -> Section {
         let mut collection: Vec<_> = self.stats.iter().collect();
         collection.sort_by(|(_, a), (_, b)| b.cmp(a));
 
-        println!("Completed Mission Sets:");
-        for (missions_raw, value) in collection.iter().take(10) {
-            let missions = Mission::unpack_missions(**missions_raw);
-            let mut mission_summary = String::new();
-
-            for mission in missions {
-                mission_summary.push_str(&format!("{}, ", mission.short_display()));
-            }
-
-            println!(
-                "    {}: {} ({:.1}%)",
-                mission_summary.trim_end_matches(", "),
-                value,
-                (**value as f32 / self.total as f32) * 100f32
-            );
+        let stats = collection
+            .iter()
+            .take(self.top)
+            .map(|(missions_raw, value)| CountStat {
+                name: mission_set_name(**missions_raw),
+                count: **value,
+                percentage: (**value as f32 / self.total as f32) * 100f32,
+            })
+            .collect();
+
+        Section {
+            key: "completed_mission_sets",
+            title: "Completed Mission Sets",
+            stats: SectionStats::Counts(stats),
         }
     }
 }
+
+/// Gets the short names of a packed set of missions, such as "Bug, BB, Seduce".
+pub(super) fn mission_set_name(missions_raw: u32) -> String {
+    let missions = Mission::unpack_missions(missions_raw);
+
+    if missions.is_empty() {
+        return "None".to_string();
+    }
+
+    missions
+        .iter()
+        .map(|mission| mission.short_display())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,74 @@
+use super::mission_set_stat_collection::mission_set_name;
+use super::{MissionSetWinStat, Section, SectionStats, StatCollection};
+use crate::query::Query;
+use crate::utils::percentage;
+use spyparty::{GameResult, Replay};
+use std::collections::HashMap;
+
+/// A collection for keeping track of how often the spy wins or gets shot after completing each
+/// set of missions, whether or not the set was enough to win.
+pub struct MissionSetWinStatCollection {
+    top: usize,
+    stats: HashMap<u32, MissionSetWins>,
+}
+
+/// The outcomes of the games in which a single set of missions was completed.
+#[derive(Default)]
+struct MissionSetWins {
+    games: u32,
+    spy_wins: u32,
+    spy_shots: u32,
+}
+
+impl MissionSetWinStatCollection {
+    /// Creates a collection which shows the `top` most common mission sets.
+    pub fn new(top: usize) -> Self {
+        MissionSetWinStatCollection {
+            top,
+            stats: HashMap::new(),
+        }
+    }
+}
+
+impl StatCollection for MissionSetWinStatCollection {
+    fn aggregate(&mut self, replay: &Replay, _: &Query) {
+        let result_data = &replay.header.result_data;
+        let wins = self
+            .stats
+            .entry(result_data.completed_missions_raw)
+            .or_default();
+
+        wins.games += 1;
+        if replay.is_spy_win() {
+            wins.spy_wins += 1;
+        }
+        if let GameResult::SpyShot = result_data.game_result {
+            wins.spy_shots += 1;
+        }
+    }
+
+    fn section(&self) -> Section {
+        let mut collection: Vec<_> = self.stats.iter().collect();
+        collection.sort_by(|(a_raw, a_wins), (b_raw, b_wins)| {
+            b_wins.games.cmp(&a_wins.games).then(a_raw.cmp(b_raw))
+        });
+
+        let stats = collection
+            .into_iter()
+            .take(self.top)
+            .map(|(missions_raw, wins)| MissionSetWinStat {
+                missions: mission_set_name(*missions_raw),
+                games: wins.games,
+                spy_wins: wins.spy_wins,
+                spy_shots: wins.spy_shots,
+                spy_win_percentage: percentage(wins.spy_wins, wins.games),
+            })
+            .collect();
+
+        Section {
+            key: "mission_set_win_rates",
+            title: "Mission Set Win Rates",
+            stats: SectionStats::MissionSets(stats),
+        }
+    }
+}
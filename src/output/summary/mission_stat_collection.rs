@@ -1,6 +1,6 @@
-use super::{increment, StatCollection};
+use super::{increment, CountStat, Section, SectionStats, StatCollection};
+use crate::query::Query;
 use crate::utils::percentage;
-use clap::ArgMatches;
 use spyparty::{Mission, Replay};
 use std::collections::HashMap;
 
@@ -12,7 +12,7 @@ pub struct MissionStatCollection {
 }
 
 impl StatCollection for MissionStatCollection {
-    fn aggregate(&mut self, replay: &Replay, _: &ArgMatches) {
+    fn aggregate(&mut self, replay: &Replay, _: &Query) {
         increment_missions(
             &mut self.stats,
             &replay.header.result_data.completed_missions,
@@ -23,7 +23,7 @@ impl StatCollection for MissionStatCollection {
         );
     }
 
-    fn print(&self) {
+    fn section(&self) -> Section {
         let mut collection: Vec<_> = self.stats.iter().collect();
         collection.sort_by(|(a_name, a_sum), (b_name, b_sum)| {
             let a_percent = percentage(**a_sum, self.total[*a_name]);
@@ -32,45 +32,50 @@ impl StatCollection for MissionStatCollection {
             b_percent
                 .partial_cmp(&a_percent)
                 .unwrap()
-                .then(a_name.cmp(&b_name))
+                .then(a_name.cmp(b_name))
         });
 
-        println!("Missions Completed:");
-        for (name, value) in collection {
-            println!(
-                "    {}: {} ({:.1}%)",
-                name,
-                value,
-                percentage(*value, self.total[name])
-            );
+        let stats = collection
+            .into_iter()
+            .map(|(name, value)| CountStat {
+                name: name.to_string(),
+                count: *value,
+                percentage: percentage(*value, self.total[name]),
+            })
+            .collect();
+
+        Section {
+            key: "missions_completed",
+            title: "Missions Completed",
+            stats: SectionStats::Counts(stats),
         }
     }
 }
 
 /// Increment a counter of how many times a mission appeared in a particular list.
-fn increment_missions<'a>(mut stats: &mut HashMap<&'a str, u32>, missions: &[Mission]) {
+pub(super) fn increment_missions(stats: &mut HashMap<&str, u32>, missions: &[Mission]) {
     if missions.contains(&Mission::BugAmbassador) {
-        increment(&mut stats, "Bug Ambassador")
+        increment(stats, "Bug Ambassador")
     }
     if missions.contains(&Mission::ContactDoubleAgent) {
-        increment(&mut stats, "Contact Double Agent")
+        increment(stats, "Contact Double Agent")
     }
     if missions.contains(&Mission::FingerprintAmbassador) {
-        increment(&mut stats, "Fingerprint Ambassador")
+        increment(stats, "Fingerprint Ambassador")
     }
     if missions.contains(&Mission::InspectStatues) {
-        increment(&mut stats, "Inspect Statues")
+        increment(stats, "Inspect Statues")
     }
     if missions.contains(&Mission::PurloinGuestList) {
-        increment(&mut stats, "Purloin Guest List")
+        increment(stats, "Purloin Guest List")
     }
     if missions.contains(&Mission::SeduceTarget) {
-        increment(&mut stats, "Seduce Target")
+        increment(stats, "Seduce Target")
     }
     if missions.contains(&Mission::SwapStatue) {
-        increment(&mut stats, "Swap Statue")
+        increment(stats, "Swap Statue")
     }
     if missions.contains(&Mission::TransferMicrofilm) {
-        increment(&mut stats, "Transfer Microfilm")
+        increment(stats, "Transfer Microfilm")
     }
 }
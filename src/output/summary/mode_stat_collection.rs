@@ -1,5 +1,5 @@
-use super::{increment, StatCollection};
-use clap::ArgMatches;
+use super::{increment, CountStat, Section, SectionStats, StatCollection};
+use crate::query::Query;
 use spyparty::{GameMode, Replay};
 use std::collections::HashMap;
 
@@ -11,7 +11,7 @@ pub struct ModeStatCollection {
 }
 
 impl StatCollection for ModeStatCollection {
-    fn aggregate(&mut self, replay: &Replay, _: &ArgMatches) {
+    fn aggregate(&mut self, replay: &Replay, _: &Query) {
         match replay.header.result_data.game_mode {
             GameMode::Any(..) => increment(&mut self.stats, "Any"),
             GameMode::Pick(..) => increment(&mut self.stats, "Pick"),
@@ -21,7 +21,7 @@ impl StatCollection for ModeStatCollection {
         self.total += 1;
     }
 
-    fn print(&self) {
-        print_single!(self, "Modes Played");
+    fn section(&self) -> Section {
+        single_section!(self, "modes", "Modes Played")
     }
 }
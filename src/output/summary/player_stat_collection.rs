@@ -1,6 +1,8 @@
-use super::StatCollection;
-use crate::utils::{has_sniper, has_spy, percentage};
-use clap::ArgMatches;
+use super::{
+    is_query_sniper, is_query_spy, query_players, PlayerStat, Section, SectionStats, StatCollection,
+};
+use crate::query::Query;
+use crate::utils::percentage;
 use spyparty::Replay;
 use std::collections::HashMap;
 
@@ -12,69 +14,39 @@ pub struct PlayerStatCollection {
 }
 
 impl StatCollection for PlayerStatCollection {
-    fn aggregate(&mut self, replay: &Replay, matches: &ArgMatches) {
-        let mut players: Vec<_> = Vec::new();
-        let mut count_spy_wins = false;
-        let mut count_sniper_wins = false;
-
-        if let Some(values) = matches.values_of("pair") {
-            count_spy_wins = true;
-            count_sniper_wins = true;
-            players = values.collect();
-        } else if let Some(values) = matches.values_of("players") {
-            count_spy_wins = true;
-            count_sniper_wins = true;
-            players = values.collect();
-        }
-
-        if let Some(values) = matches.values_of("spies") {
-            count_spy_wins = true;
-            players.append(&mut values.collect());
-        }
-
-        if let Some(values) = matches.values_of("snipers") {
-            count_sniper_wins = true;
-            players.append(&mut values.collect());
-        }
+    fn aggregate(&mut self, replay: &Replay, query: &Query) {
+        let (players, count_spy_wins, count_sniper_wins) = query_players(query);
 
         // Only add up wins and losses where a player was a spy.
-        if count_spy_wins {
-            for player in &players {
-                if has_spy(&replay, player) {
-                    let user_name = &replay.header.spy_user_name;
-                    let display_name = &replay.spy_name();
-
-                    if replay.is_spy_win() {
-                        add_win(&mut self.stats, user_name, display_name);
-                    } else if replay.is_sniper_win() {
-                        add_loss(&mut self.stats, user_name, display_name)
-                    }
-
-                    increment_total(&mut self.totals, user_name);
-                }
+        if count_spy_wins && is_query_spy(replay, query, &players) {
+            let user_name = &replay.header.spy_user_name;
+            let display_name = &replay.spy_name();
+
+            if replay.is_spy_win() {
+                add_win(&mut self.stats, user_name, display_name);
+            } else if replay.is_sniper_win() {
+                add_loss(&mut self.stats, user_name, display_name)
             }
+
+            increment_total(&mut self.totals, user_name);
         }
 
         // Only add up wins and losses where a player was a sniper.
-        if count_sniper_wins {
-            for player in &players {
-                if has_sniper(&replay, player) {
-                    let user_name = &replay.header.sniper_user_name;
-                    let display_name = &replay.sniper_name();
-
-                    if replay.is_sniper_win() {
-                        add_win(&mut self.stats, user_name, display_name);
-                    } else if replay.is_spy_win() {
-                        add_loss(&mut self.stats, user_name, display_name)
-                    }
-
-                    increment_total(&mut self.totals, user_name);
-                }
+        if count_sniper_wins && is_query_sniper(replay, query, &players) {
+            let user_name = &replay.header.sniper_user_name;
+            let display_name = &replay.sniper_name();
+
+            if replay.is_sniper_win() {
+                add_win(&mut self.stats, user_name, display_name);
+            } else if replay.is_spy_win() {
+                add_loss(&mut self.stats, user_name, display_name)
             }
+
+            increment_total(&mut self.totals, user_name);
         }
     }
 
-    fn print(&self) {
+    fn section(&self) -> Section {
         let mut collection: Vec<_> = self.stats.iter().collect();
         collection.sort_unstable_by(
             |(a_name, (a_display, a_wins, _)), (b_name, (b_display, b_wins, _))| {
@@ -84,19 +56,25 @@ impl StatCollection for PlayerStatCollection {
                 b_percent
                     .partial_cmp(&a_percent)
                     .unwrap()
-                    .then(a_display.cmp(&b_display))
+                    .then(a_display.cmp(b_display))
             },
         );
 
-        println!("Player Stats:");
-        for (user_name, (display_name, wins, losses)) in collection {
-            println!(
-                "    {}: {}W {}L ({:.1}%)",
-                display_name,
-                wins,
-                losses,
-                percentage(*wins, self.totals[user_name])
-            );
+        let stats = collection
+            .into_iter()
+            .map(|(user_name, (display_name, wins, losses))| PlayerStat {
+                user_name: user_name.to_string(),
+                display_name: display_name.to_string(),
+                wins: *wins,
+                losses: *losses,
+                win_percentage: percentage(*wins, self.totals[user_name]),
+            })
+            .collect();
+
+        Section {
+            key: "players",
+            title: "Player Stats",
+            stats: SectionStats::Players(stats),
         }
     }
 }
@@ -0,0 +1,285 @@
+use super::{CountStat, Section, SectionStats, Summary};
+use crate::errors::*;
+use crate::output::form::streak;
+use std::io;
+
+/// Prints a summary as plain text.
+pub fn text(summary: &Summary) {
+    if summary.total == 0 {
+        println!("No replays found.");
+        return;
+    }
+
+    println!("Total Replays:\n    {}", summary.total);
+
+    for section in &summary.sections {
+        println!("{}:", section.title);
+
+        match &section.stats {
+            SectionStats::Counts(stats) => {
+                for stat in stats {
+                    println!(
+                        "    {}: {} ({:.1}%)",
+                        stat.name, stat.count, stat.percentage
+                    );
+                }
+            }
+            SectionStats::Players(stats) => {
+                for stat in stats {
+                    println!(
+                        "    {}: {}W {}L ({:.1}%)",
+                        stat.display_name, stat.wins, stat.losses, stat.win_percentage
+                    );
+                }
+            }
+            SectionStats::Clock(stat) => {
+                let average_duration = stat.average_duration.round() as u32;
+                let minutes = average_duration / 60;
+                let seconds = average_duration % 60;
+
+                println!("    Average Duration: {}m{}s", minutes, seconds);
+
+                match stat.clock_usage_percentage {
+                    Some(clock_usage) => println!("    Clock Usage: {:.1}%", clock_usage),
+                    None => println!("    Clock Usage: Unknown"),
+                }
+            }
+            SectionStats::MapWins(stats) => {
+                for stat in stats {
+                    println!(
+                        "    {}: {} spy wins ({:.1}%), {} sniper wins ({:.1}%)",
+                        stat.map,
+                        stat.spy_wins,
+                        stat.spy_win_percentage,
+                        stat.sniper_wins,
+                        stat.sniper_win_percentage
+                    );
+                    println!("        Results: {}", join_results(&stat.results));
+
+                    for player in &stat.players {
+                        println!(
+                            "        {}: {}W {}L as spy, {}W {}L as sniper",
+                            player.display_name,
+                            player.spy_wins,
+                            player.spy_losses,
+                            player.sniper_wins,
+                            player.sniper_losses
+                        );
+                    }
+                }
+            }
+            SectionStats::Groups(groups) => {
+                for group in groups {
+                    println!("    {} ({} games):", group.name, group.games);
+
+                    for stat in &group.stats {
+                        println!(
+                            "        {}: {} ({:.1}%)",
+                            stat.name, stat.count, stat.percentage
+                        );
+                    }
+                }
+            }
+            SectionStats::MissionSets(stats) => {
+                for stat in stats {
+                    println!(
+                        "    {}: {} games, {} spy wins ({:.1}%), {} shots",
+                        stat.missions,
+                        stat.games,
+                        stat.spy_wins,
+                        stat.spy_win_percentage,
+                        stat.spy_shots
+                    );
+                }
+            }
+            SectionStats::Form(stats) => {
+                for stat in stats {
+                    println!(
+                        "    {}: {}W {}L, {:.1}% in the last {} games, current streak {}",
+                        stat.role,
+                        stat.wins,
+                        stat.losses,
+                        stat.recent_win_percentage,
+                        stat.recent_games,
+                        streak(stat.current_streak)
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Prints a summary as a JSON object.
+pub fn json(summary: &Summary) -> Result<()> {
+    serde_json::to_writer_pretty(io::stdout(), summary).chain_err(|| "failed to write JSON")?;
+    println!();
+    Ok(())
+}
+
+/// Prints a summary as a YAML document.
+pub fn yaml(summary: &Summary) -> Result<()> {
+    serde_yaml::to_writer(io::stdout(), summary).chain_err(|| "failed to write YAML")?;
+    println!();
+    Ok(())
+}
+
+/// Prints a summary as a set of Markdown tables, one for each section.
+pub fn markdown(summary: &Summary) {
+    if summary.total == 0 {
+        println!("No replays found.");
+        return;
+    }
+
+    println!("**Total Replays:** {}", summary.total);
+
+    for section in &summary.sections {
+        println!();
+        markdown_section(section);
+    }
+}
+
+/// Prints a single section of a summary as a Markdown table.
+fn markdown_section(section: &Section) {
+    println!("### {}\n", section.title);
+
+    match &section.stats {
+        SectionStats::Counts(stats) => {
+            println!("| Name | Count | Percentage |");
+            println!("| --- | ---: | ---: |");
+            for stat in stats {
+                println!(
+                    "| {} | {} | {:.1}% |",
+                    escape(&stat.name),
+                    stat.count,
+                    stat.percentage
+                );
+            }
+        }
+        SectionStats::Players(stats) => {
+            println!("| Player | Wins | Losses | Win Rate |");
+            println!("| --- | ---: | ---: | ---: |");
+            for stat in stats {
+                println!(
+                    "| {} | {} | {} | {:.1}% |",
+                    escape(&stat.display_name),
+                    stat.wins,
+                    stat.losses,
+                    stat.win_percentage
+                );
+            }
+        }
+        SectionStats::Clock(stat) => {
+            let average_duration = stat.average_duration.round() as u32;
+
+            println!("| Stat | Value |");
+            println!("| --- | ---: |");
+            println!(
+                "| Average Duration | {}m{}s |",
+                average_duration / 60,
+                average_duration % 60
+            );
+            match stat.clock_usage_percentage {
+                Some(clock_usage) => println!("| Clock Usage | {:.1}% |", clock_usage),
+                None => println!("| Clock Usage | Unknown |"),
+            }
+        }
+        SectionStats::MapWins(stats) => {
+            println!("| Map | Games | Spy Wins | Sniper Wins | Spy Win Rate | Results |");
+            println!("| --- | ---: | ---: | ---: | ---: | --- |");
+            for stat in stats {
+                println!(
+                    "| {} | {} | {} | {} | {:.1}% | {} |",
+                    escape(&stat.map),
+                    stat.games,
+                    stat.spy_wins,
+                    stat.sniper_wins,
+                    stat.spy_win_percentage,
+                    escape(&join_results(&stat.results))
+                );
+            }
+
+            if stats.iter().any(|stat| !stat.players.is_empty()) {
+                println!();
+                println!("| Map | Player | Spy W-L | Sniper W-L |");
+                println!("| --- | --- | ---: | ---: |");
+                for stat in stats {
+                    for player in &stat.players {
+                        println!(
+                            "| {} | {} | {}-{} | {}-{} |",
+                            escape(&stat.map),
+                            escape(&player.display_name),
+                            player.spy_wins,
+                            player.spy_losses,
+                            player.sniper_wins,
+                            player.sniper_losses
+                        );
+                    }
+                }
+            }
+        }
+        SectionStats::Groups(groups) => {
+            println!("| Group | Games | Name | Count | Percentage |");
+            println!("| --- | ---: | --- | ---: | ---: |");
+            for group in groups {
+                for stat in &group.stats {
+                    println!(
+                        "| {} | {} | {} | {} | {:.1}% |",
+                        escape(&group.name),
+                        group.games,
+                        escape(&stat.name),
+                        stat.count,
+                        stat.percentage
+                    );
+                }
+            }
+        }
+        SectionStats::MissionSets(stats) => {
+            println!("| Missions | Games | Spy Wins | Spy Shots | Spy Win Rate |");
+            println!("| --- | ---: | ---: | ---: | ---: |");
+            for stat in stats {
+                println!(
+                    "| {} | {} | {} | {} | {:.1}% |",
+                    escape(&stat.missions),
+                    stat.games,
+                    stat.spy_wins,
+                    stat.spy_shots,
+                    stat.spy_win_percentage
+                );
+            }
+        }
+        SectionStats::Form(stats) => {
+            println!("| Role | Wins | Losses | Recent Games | Recent Win Rate | Streak |");
+            println!("| --- | ---: | ---: | ---: | ---: | --- |");
+            for stat in stats {
+                println!(
+                    "| {} | {} | {} | {} | {:.1}% | {} |",
+                    stat.role,
+                    stat.wins,
+                    stat.losses,
+                    stat.recent_games,
+                    stat.recent_win_percentage,
+                    streak(stat.current_streak)
+                );
+            }
+        }
+    }
+}
+
+/// Joins the counts of each result together, such as "Spy Shot 5 (50.0%), Missions Win 5 (50.0%)".
+fn join_results(results: &[CountStat]) -> String {
+    results
+        .iter()
+        .map(|result| {
+            format!(
+                "{} {} ({:.1}%)",
+                result.name, result.count, result.percentage
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Escapes any characters in a table cell which would otherwise break the table.
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|")
+}
@@ -1,5 +1,5 @@
-use super::{increment, StatCollection};
-use clap::ArgMatches;
+use super::{increment, CountStat, Section, SectionStats, StatCollection};
+use crate::query::Query;
 use spyparty::{GameResult, Replay};
 use std::collections::HashMap;
 
@@ -11,19 +11,27 @@ pub struct ResultStatCollection {
 }
 
 impl StatCollection for ResultStatCollection {
-    fn aggregate(&mut self, replay: &Replay, _: &ArgMatches) {
-        match replay.header.result_data.game_result {
-            GameResult::MissionsWin => increment(&mut self.stats, "Missions Win"),
-            GameResult::SpyShot => increment(&mut self.stats, "Spy Shot"),
-            GameResult::SpyTimeout => increment(&mut self.stats, "Spy Timeout"),
-            GameResult::CivilianShot => increment(&mut self.stats, "Civilian Shot"),
-            GameResult::InProgress => increment(&mut self.stats, "Unfinished"),
-        }
+    fn aggregate(&mut self, replay: &Replay, _: &Query) {
+        increment(
+            &mut self.stats,
+            result_name(&replay.header.result_data.game_result),
+        );
 
         self.total += 1;
     }
 
-    fn print(&self) {
-        print_single!(self, "Results");
+    fn section(&self) -> Section {
+        single_section!(self, "results", "Results")
+    }
+}
+
+/// Gets the readable name of a game result.
+pub(crate) fn result_name(result: &GameResult) -> &'static str {
+    match result {
+        GameResult::MissionsWin => "Missions Win",
+        GameResult::SpyShot => "Spy Shot",
+        GameResult::SpyTimeout => "Spy Timeout",
+        GameResult::CivilianShot => "Civilian Shot",
+        GameResult::InProgress => "Unfinished",
     }
 }
@@ -0,0 +1,50 @@
+use super::mission_stat_collection::increment_missions;
+use super::{CountStat, Section, SectionStats, StatCollection};
+use crate::query::Query;
+use crate::utils::percentage;
+use spyparty::Replay;
+use std::collections::HashMap;
+
+/// A collection for keeping track of missions which were picked but never completed.
+#[derive(Default)]
+pub struct UncompletedMissionStatCollection {
+    total: u32,
+    selected: HashMap<&'static str, u32>,
+    completed: HashMap<&'static str, u32>,
+}
+
+impl StatCollection for UncompletedMissionStatCollection {
+    fn aggregate(&mut self, replay: &Replay, _: &Query) {
+        let result_data = &replay.header.result_data;
+
+        increment_missions(&mut self.selected, &result_data.selected_missions);
+        increment_missions(&mut self.completed, &result_data.completed_missions);
+        self.total += 1;
+    }
+
+    fn section(&self) -> Section {
+        let mut collection: Vec<_> = self
+            .selected
+            .iter()
+            .filter(|(name, _)| !self.completed.contains_key(*name))
+            .collect();
+        collection
+            .sort_by(|(a_name, a_sum), (b_name, b_sum)| b_sum.cmp(a_sum).then(a_name.cmp(b_name)));
+
+        // The count is how many games the mission was picked in
+        let stats = collection
+            .into_iter()
+            .map(|(name, value)| CountStat {
+                name: name.to_string(),
+                count: *value,
+                percentage: percentage(*value, self.total),
+            })
+            .collect();
+
+        Section {
+            key: "missions_never_completed",
+            title: "Missions Picked but Never Completed",
+            stats: SectionStats::Counts(stats),
+        }
+    }
+}
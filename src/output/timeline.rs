@@ -0,0 +1,152 @@
+use crate::errors::*;
+use crate::output::summary::result_name;
+use crate::output::TableFormat;
+use crate::utils::{percentage, start_time};
+use crate::MatchedReplay;
+use prettytable::{Cell, Row, Table};
+use spyparty::{GameResult, Replay};
+use std::collections::{BTreeMap, HashMap};
+
+/// The results shown for each period, in the order they are shown.
+const RESULTS: [GameResult; 5] = [
+    GameResult::MissionsWin,
+    GameResult::CivilianShot,
+    GameResult::SpyShot,
+    GameResult::SpyTimeout,
+    GameResult::InProgress,
+];
+
+/// The length of time replays are grouped by in a timeline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Period {
+    Day,
+    /// An ISO week, starting on Monday.
+    Week,
+    Month,
+}
+
+/// The games played in a single period of a timeline.
+#[derive(Default)]
+pub struct PeriodStats {
+    pub games: u32,
+    pub spy_wins: u32,
+    /// How many games ended in each result, by the name of the result.
+    pub results: HashMap<&'static str, u32>,
+    pub total_duration: f32,
+}
+
+impl Period {
+    /// Parses the name of a period.
+    pub fn parse(value: &str) -> Result<Period> {
+        let period = match value.to_ascii_lowercase().as_str() {
+            "day" => Period::Day,
+            "week" => Period::Week,
+            "month" => Period::Month,
+            _ => bail!("'{}' is not a valid period for a timeline", value),
+        };
+
+        Ok(period)
+    }
+
+    /// The title of the column for the period.
+    fn title(self) -> &'static str {
+        match self {
+            Period::Day => "DAY",
+            Period::Week => "WEEK",
+            Period::Month => "MONTH",
+        }
+    }
+
+    /// Gets the period a replay started in, such as "2019-03-14", "2019-W11" or "2019-03".
+    pub fn value(self, replay: &Replay) -> String {
+        let format = match self {
+            Period::Day => "%Y-%m-%d",
+            Period::Week => "%G-W%V",
+            Period::Month => "%Y-%m",
+        };
+
+        start_time(replay).format(format).to_string()
+    }
+}
+
+impl PeriodStats {
+    /// The average length of the games in the period, in seconds.
+    pub fn average_duration(&self) -> f32 {
+        self.total_duration / self.games as f32
+    }
+}
+
+/// Groups replays by the period they started in, sorted from the earliest period to the latest.
+///
+/// Periods without any games are left out.
+pub fn periods(replays: &[MatchedReplay], period: Period) -> BTreeMap<String, PeriodStats> {
+    let mut periods: BTreeMap<String, PeriodStats> = BTreeMap::new();
+
+    for replay in replays {
+        let replay = &replay.inner;
+        let stats = periods.entry(period.value(replay)).or_default();
+
+        stats.games += 1;
+        if replay.is_spy_win() {
+            stats.spy_wins += 1;
+        }
+        *stats
+            .results
+            .entry(result_name(&replay.header.result_data.game_result))
+            .or_insert(0) += 1;
+        stats.total_duration += replay.header.duration;
+    }
+
+    periods
+}
+
+/// Shows the number of games, spy win rate, results and average duration of each period.
+pub fn show(replays: &[MatchedReplay], period: Period, format: TableFormat) -> Result<()> {
+    let mut table = Table::new();
+    let mut header = Row::empty();
+
+    header.add_cell(Cell::new(period.title()));
+    header.add_cell(Cell::new("GAMES"));
+    header.add_cell(Cell::new("SPY WIN %"));
+    for result in &RESULTS {
+        header.add_cell(Cell::new(&result_name(result).to_uppercase()));
+    }
+    header.add_cell(Cell::new("AVG DURATION"));
+    table.add_row(header);
+
+    for (name, stats) in periods(replays, period) {
+        let mut row = Row::empty();
+        let average_duration = stats.average_duration().round() as u32;
+
+        row.add_cell(Cell::new(&name));
+        row.add_cell(Cell::new(&stats.games.to_string()));
+        row.add_cell(Cell::new(&format!(
+            "{:.1}",
+            percentage(stats.spy_wins, stats.games)
+        )));
+        for result in &RESULTS {
+            let count = stats.results.get(result_name(result)).unwrap_or(&0);
+            row.add_cell(Cell::new(&count.to_string()));
+        }
+        row.add_cell(Cell::new(&format!(
+            "{}m{}s",
+            average_duration / 60,
+            average_duration % 60
+        )));
+
+        table.add_row(row);
+    }
+
+    match format {
+        TableFormat::Text => {
+            table.printstd();
+        }
+        TableFormat::Csv => {
+            table
+                .to_csv(std::io::stdout())
+                .chain_err(|| "failed to write timeline table")?;
+        }
+    }
+
+    Ok(())
+}
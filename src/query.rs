@@ -0,0 +1,629 @@
+use crate::aliases::Aliases;
+use crate::errors::*;
+use crate::expression::Expression;
+use crate::filters;
+use crate::matches::MatchResult;
+use crate::utils::has_player;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Weekday};
+use regex::{Regex, RegexBuilder};
+use spyparty::{GameMode, GameResult, Map, Mission, Replay};
+use std::convert::TryInto;
+use std::str::FromStr;
+
+/// A typed description of which replays should be matched.
+///
+/// Every field that is left empty (or `false`) does not restrict the matched replays at all, so
+/// `Query::new()` matches every replay. Values within a single field are combined with OR, except
+/// for `pair` and `completed_missions_all` which are combined with AND. Separate fields are always
+/// combined with AND.
+///
+/// Player names are matched against both the user name and display name of a player, ignoring
+/// case, and can be glob patterns such as `check*` (see `utils::name_matches`).
+#[derive(Default)]
+pub struct Query {
+    /// Players' names (either spy or sniper), ANY of which must be present.
+    pub players: Vec<String>,
+    /// Players' names, ALL of which must be present.
+    pub pair: Vec<String>,
+    /// Spies' names, ANY of which must be the spy.
+    pub spies: Vec<String>,
+    /// Snipers' names, ANY of which must be the sniper.
+    pub snipers: Vec<String>,
+    /// Regular expressions, ANY of which must match the name of the spy or sniper.
+    pub players_regex: Vec<Regex>,
+    /// Maps, ANY of which must be the map played.
+    pub maps: Vec<Map>,
+    /// Game modes, ANY of which must be the mode played.
+    pub modes: Vec<ModeFilter>,
+    /// Results, ANY of which must be the result of the game.
+    pub results: Vec<GameResult>,
+    /// Missions, ANY of which must have been completed.
+    pub completed_missions: Vec<Mission>,
+    /// Missions, ALL of which must have been completed.
+    pub completed_missions_all: Vec<Mission>,
+    /// Only match games that end in a spy win.
+    pub spy_win: bool,
+    /// Only match games that end in a sniper win.
+    pub sniper_win: bool,
+    /// Only match games that end with the mission win countdown.
+    pub countdown: bool,
+    /// Only match games that started at or after this time.
+    pub since: Option<DateTime<Local>>,
+    /// Only match games that started at or before this time.
+    pub until: Option<DateTime<Local>>,
+    /// Days of the week, ANY of which must be the day the game started on.
+    pub days_of_week: Vec<Weekday>,
+    /// An inclusive range of hours of the day the game must have started in. The range wraps
+    /// around midnight if the start is after the end.
+    pub hour_range: Option<(u32, u32)>,
+    /// The range of lengths of the game, in seconds.
+    pub duration: Option<ValueRange<f32>>,
+    /// The range of starting clocks of the game, in seconds.
+    pub clock: Option<ValueRange<u32>>,
+    /// The range of fractions of the starting clock that were used.
+    pub clock_usage: Option<ValueRange<f32>>,
+    /// A boolean expression of conditions the game must satisfy.
+    pub expression: Option<Expression>,
+    /// A query whose filters must each NOT match the game, such as excluding a set of maps. Each
+    /// filter is negated on its own, so excluding two maps and a player removes games on either
+    /// map as well as games with that player.
+    pub exclude: Option<Box<Query>>,
+    /// Only match games from matches (consecutive games between the same pair of players) that
+    /// ended in this result for the first player in `players` or `pair`. Unlike the other
+    /// filters, this is applied to the whole collection of matched replays after they have been
+    /// parsed.
+    pub match_result: Option<MatchResult>,
+    /// Keep every game of a match if any of its games are matched, so that the score of each
+    /// match isn't changed by the other filters. Like `match_result`, this is applied after the
+    /// replays have been parsed.
+    pub whole_matches: bool,
+    /// Aliases which merge the accounts of players into a single identity. Player names in the
+    /// query are resolved to their identities, as are the user names in matched replays.
+    pub aliases: Aliases,
+}
+
+/// A game mode to filter on, either a whole category of modes or one exact mode.
+pub enum ModeFilter {
+    /// Any "Any x/y" mode.
+    Any,
+    /// Any "Pick x/y" mode.
+    Pick,
+    /// Any "Known x" mode.
+    Known,
+    /// A specific mode, such as "a4/8".
+    Exact(GameMode),
+}
+
+/// An inclusive range of values, either end of which can be left open.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ValueRange<T> {
+    /// The smallest value in the range.
+    pub min: Option<T>,
+    /// The largest value in the range.
+    pub max: Option<T>,
+}
+
+impl<T: PartialOrd + Copy> ValueRange<T> {
+    /// Creates a range that contains only a single value.
+    pub fn exactly(value: T) -> Self {
+        ValueRange {
+            min: Some(value),
+            max: Some(value),
+        }
+    }
+
+    /// Checks if a value is within the range.
+    pub fn contains(&self, value: T) -> bool {
+        let above_min = match self.min {
+            Some(min) => value >= min,
+            None => true,
+        };
+        let below_max = match self.max {
+            Some(max) => value <= max,
+            None => true,
+        };
+
+        above_min && below_max
+    }
+}
+
+impl Query {
+    /// Creates a query which matches every replay.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds players' names, ANY of which must be present (as either spy or sniper).
+    pub fn players<I, S>(mut self, players: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.players.extend(players.into_iter().map(Into::into));
+        self.aliases.resolve(&mut self.players);
+        self
+    }
+
+    /// Sets a pair of players who must have played together.
+    pub fn pair<S: Into<String>>(mut self, first: S, second: S) -> Self {
+        self.pair
+            .push(self.aliases.identity(&first.into()).to_string());
+        self.pair
+            .push(self.aliases.identity(&second.into()).to_string());
+        self
+    }
+
+    /// Adds spies' names, ANY of which must be the spy.
+    pub fn spies<I, S>(mut self, spies: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.spies.extend(spies.into_iter().map(Into::into));
+        self.aliases.resolve(&mut self.spies);
+        self
+    }
+
+    /// Adds snipers' names, ANY of which must be the sniper.
+    pub fn snipers<I, S>(mut self, snipers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.snipers.extend(snipers.into_iter().map(Into::into));
+        self.aliases.resolve(&mut self.snipers);
+        self
+    }
+
+    /// Adds regular expressions, ANY of which must match the user name or display name of the spy
+    /// or sniper.
+    pub fn players_regex<I: IntoIterator<Item = Regex>>(mut self, regexes: I) -> Self {
+        self.players_regex.extend(regexes);
+        self
+    }
+
+    /// Adds maps, ANY of which must be the map played.
+    pub fn maps<I: IntoIterator<Item = Map>>(mut self, maps: I) -> Self {
+        self.maps.extend(maps);
+        self
+    }
+
+    /// Adds game modes, ANY of which must be the mode played.
+    pub fn modes<I: IntoIterator<Item = ModeFilter>>(mut self, modes: I) -> Self {
+        self.modes.extend(modes);
+        self
+    }
+
+    /// Adds results, ANY of which must be the result of the game.
+    pub fn results<I: IntoIterator<Item = GameResult>>(mut self, results: I) -> Self {
+        self.results.extend(results);
+        self
+    }
+
+    /// Adds missions, ANY of which must have been completed.
+    pub fn completed_missions<I: IntoIterator<Item = Mission>>(mut self, missions: I) -> Self {
+        self.completed_missions.extend(missions);
+        self
+    }
+
+    /// Adds missions, ALL of which must have been completed.
+    pub fn completed_missions_all<I: IntoIterator<Item = Mission>>(mut self, missions: I) -> Self {
+        self.completed_missions_all.extend(missions);
+        self
+    }
+
+    /// Sets whether only games ending in a spy win are matched.
+    pub fn spy_win(mut self, spy_win: bool) -> Self {
+        self.spy_win = spy_win;
+        self
+    }
+
+    /// Sets whether only games ending in a sniper win are matched.
+    pub fn sniper_win(mut self, sniper_win: bool) -> Self {
+        self.sniper_win = sniper_win;
+        self
+    }
+
+    /// Sets whether only games ending with the mission win countdown are matched.
+    pub fn countdown(mut self, countdown: bool) -> Self {
+        self.countdown = countdown;
+        self
+    }
+
+    /// Sets the time games must have started at or after.
+    pub fn since(mut self, since: DateTime<Local>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Sets the time games must have started at or before.
+    pub fn until(mut self, until: DateTime<Local>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Adds days of the week, ANY of which must be the day the game started on.
+    pub fn days_of_week<I: IntoIterator<Item = Weekday>>(mut self, days: I) -> Self {
+        self.days_of_week.extend(days);
+        self
+    }
+
+    /// Sets the inclusive range of hours of the day games must have started in.
+    pub fn hour_range(mut self, start: u32, end: u32) -> Self {
+        self.hour_range = Some((start, end));
+        self
+    }
+
+    /// Sets the range of lengths of the game, in seconds.
+    pub fn duration(mut self, duration: ValueRange<f32>) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Sets the range of starting clocks of the game, in seconds.
+    pub fn clock(mut self, clock: ValueRange<u32>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Sets the range of fractions of the starting clock that were used.
+    pub fn clock_usage(mut self, clock_usage: ValueRange<f32>) -> Self {
+        self.clock_usage = Some(clock_usage);
+        self
+    }
+
+    /// Sets a boolean expression of conditions games must satisfy.
+    pub fn expression(mut self, mut expression: Expression) -> Self {
+        expression.resolve_aliases(&self.aliases);
+        self.expression = Some(expression);
+        self
+    }
+
+    /// Sets a query whose filters must each NOT match the game.
+    pub fn exclude(mut self, mut exclude: Query) -> Self {
+        exclude.resolve_aliases(&self.aliases);
+        self.exclude = Some(Box::new(exclude));
+        self
+    }
+
+    /// Sets the result the first player must have had in the match a game was part of.
+    pub fn match_result(mut self, match_result: MatchResult) -> Self {
+        self.match_result = Some(match_result);
+        self
+    }
+
+    /// Keeps every game of the matches that any matched games are part of.
+    pub fn whole_matches(mut self) -> Self {
+        self.whole_matches = true;
+        self
+    }
+
+    /// Checks if the replays have to be grouped into matches before they can be filtered.
+    pub fn groups_matches(&self) -> bool {
+        self.match_result.is_some() || self.whole_matches
+    }
+
+    /// Sets the aliases which merge the accounts of players into a single identity, resolving the
+    /// player names already in the query (and any added later) to their identities.
+    pub fn aliases(mut self, aliases: Aliases) -> Self {
+        self.resolve_aliases(&aliases);
+        self.aliases = aliases;
+        self
+    }
+
+    /// Resolves every player name in the query, including in its expression and the query it
+    /// excludes, to their identities.
+    pub(crate) fn resolve_aliases(&mut self, aliases: &Aliases) {
+        aliases.resolve(&mut self.players);
+        for name in &mut self.pair {
+            *name = aliases.identity(name).to_string();
+        }
+        aliases.resolve(&mut self.spies);
+        aliases.resolve(&mut self.snipers);
+
+        if let Some(expression) = &mut self.expression {
+            expression.resolve_aliases(aliases);
+        }
+        if let Some(exclude) = &mut self.exclude {
+            exclude.resolve_aliases(aliases);
+        }
+    }
+
+    /// Gets the player that match-level filters and outputs are from the point of view of.
+    pub fn match_player(&self) -> Option<&str> {
+        self.players
+            .first()
+            .or_else(|| self.pair.first())
+            .map(String::as_str)
+    }
+
+    /// Gets every player named in the player-based filters, without duplicates.
+    pub fn named_players(&self) -> Vec<&str> {
+        let mut players: Vec<&str> = Vec::new();
+
+        for player in self
+            .players
+            .iter()
+            .chain(&self.pair)
+            .chain(&self.spies)
+            .chain(&self.snipers)
+        {
+            if !players.contains(&player.as_str()) {
+                players.push(player);
+            }
+        }
+
+        players
+    }
+
+    /// Checks if any of the player-based filters are in use.
+    pub fn has_player_filters(&self) -> bool {
+        !self.players.is_empty()
+            || !self.pair.is_empty()
+            || !self.spies.is_empty()
+            || !self.snipers.is_empty()
+            || !self.players_regex.is_empty()
+    }
+
+    /// Checks if a replay is matched by this query.
+    pub fn matches(&self, replay: &Replay) -> bool {
+        filters::filter(replay, self)
+    }
+
+    /// Checks if a replay should be kept while the replays are being parsed, before they are
+    /// grouped into matches. Matches have to be grouped from every game rather than just the
+    /// matched ones, so that the other filters don't change their scores, which means only the
+    /// games of the player they are for can be left out until the rest of the query is applied.
+    pub fn prefilter(&self, replay: &Replay) -> bool {
+        if !self.groups_matches() {
+            return self.matches(replay);
+        }
+
+        match self.match_player() {
+            Some(player) => has_player(replay, player),
+            None => true,
+        }
+    }
+}
+
+/// Parses a regular expression to match players' names against, ignoring case.
+pub fn parse_player_regex(value: &str) -> Result<Regex> {
+    RegexBuilder::new(value)
+        .case_insensitive(true)
+        .build()
+        .chain_err(|| format!("'{}' is not a valid regular expression", value))
+}
+
+/// Parses a map name, also accepting "unknown" to match any unrecognised map.
+pub fn parse_map(value: &str) -> Result<Map> {
+    if let Ok(map) = value.try_into() {
+        Ok(map)
+    } else if value.eq_ignore_ascii_case("unknown") {
+        Ok(Map::Unknown(0))
+    } else {
+        bail!("'{}' is not a valid option for the map filter", value)
+    }
+}
+
+/// Parses a game mode, either a category ("any", "pick" or "known") or an exact mode.
+pub fn parse_mode(value: &str) -> Result<ModeFilter> {
+    match value {
+        "any" | "a" => Ok(ModeFilter::Any),
+        "pick" | "p" => Ok(ModeFilter::Pick),
+        "known" | "k" => Ok(ModeFilter::Known),
+        _ => {
+            if let Ok(mode) = value.try_into() {
+                Ok(ModeFilter::Exact(mode))
+            } else {
+                bail!("'{}' is not a valid option for the game mode filter", value)
+            }
+        }
+    }
+}
+
+/// Parses the result of a game.
+pub fn parse_result(value: &str) -> Result<GameResult> {
+    if let Ok(result) = value.try_into() {
+        Ok(result)
+    } else {
+        bail!("'{}' is not a valid option for the result filter", value)
+    }
+}
+
+/// Parses the result of a match.
+pub fn parse_match_result(value: &str) -> Result<MatchResult> {
+    match value.to_ascii_lowercase().as_str() {
+        "win" | "won" => Ok(MatchResult::Win),
+        "loss" | "lost" => Ok(MatchResult::Loss),
+        "draw" | "drawn" => Ok(MatchResult::Draw),
+        _ => bail!("'{}' is not a valid match result", value),
+    }
+}
+
+/// Parses a mission name.
+pub fn parse_mission(value: &str) -> Result<Mission> {
+    if let Ok(mission) = value.try_into() {
+        Ok(mission)
+    } else {
+        bail!("'{}' is not a valid option for the mission filter", value)
+    }
+}
+
+/// Parses the earliest start time of a game, either an absolute date (and optional time) such as
+/// "2019-03-01" or "2019-03-01 20:00", or a time relative to now such as "12h", "30d" or "2w".
+pub fn parse_since(value: &str) -> Result<DateTime<Local>> {
+    parse_time(value, false).chain_err(|| format!("'{}' is not a valid option for --since", value))
+}
+
+/// Parses the latest start time of a game, in the same formats as `parse_since`. A date on its own
+/// includes the whole of that day.
+pub fn parse_until(value: &str) -> Result<DateTime<Local>> {
+    parse_time(value, true).chain_err(|| format!("'{}' is not a valid option for --until", value))
+}
+
+/// Parses a day of the week, such as "mon" or "Monday".
+pub fn parse_day_of_week(value: &str) -> Result<Weekday> {
+    if let Ok(day) = value.parse() {
+        Ok(day)
+    } else {
+        bail!("'{}' is not a valid day of the week", value)
+    }
+}
+
+/// Parses an inclusive range of hours of the day, such as "20" or "18..23".
+pub fn parse_hour_range(value: &str) -> Result<(u32, u32)> {
+    let mut hours = value.splitn(2, "..");
+    let start = hours.next().unwrap_or_default();
+    let end = hours.next().unwrap_or(start);
+
+    match (start.trim().parse(), end.trim().parse()) {
+        (Ok(start), Ok(end)) if start < 24 && end < 24 => Ok((start, end)),
+        _ => bail!("'{}' is not a valid range of hours", value),
+    }
+}
+
+/// Parses a length of time in seconds, either as a plain number of seconds (e.g. "90") or in
+/// minutes and seconds (e.g. "1m30s", "2m" or "45s").
+pub fn parse_duration(value: &str) -> Result<f32> {
+    let invalid = || format!("'{}' is not a valid duration", value);
+
+    let seconds = if let Ok(seconds) = value.parse() {
+        seconds
+    } else {
+        let (minutes, seconds) = match value.find('m') {
+            Some(index) => (&value[..index], &value[index + 1..]),
+            None => ("0", value),
+        };
+        let minutes: f32 = minutes.parse().chain_err(invalid)?;
+        let seconds: f32 = match seconds {
+            "" => 0.0,
+            _ if seconds.ends_with('s') => {
+                seconds[..seconds.len() - 1].parse().chain_err(invalid)?
+            }
+            _ => bail!(invalid()),
+        };
+        minutes * 60.0 + seconds
+    };
+
+    if seconds.is_finite() && seconds > 0.0 {
+        Ok(seconds)
+    } else {
+        bail!("{}, it must be a positive length of time", invalid())
+    }
+}
+
+/// Parses a starting clock in seconds, either a single value (e.g. "210") or an inclusive range
+/// (e.g. "180..240", "..180" or "240..").
+pub fn parse_clock(value: &str) -> Result<ValueRange<u32>> {
+    parse_range(value, parse_number).chain_err(|| format!("'{}' is not a valid clock", value))
+}
+
+/// Parses a range of fractions of the starting clock that were used (e.g. "0.9..", "..0.5" or
+/// "0.25..0.75").
+pub fn parse_clock_usage(value: &str) -> Result<ValueRange<f32>> {
+    parse_range(value, parse_number).chain_err(|| format!("'{}' is not a valid clock usage", value))
+}
+
+/// Parses a range of lengths of time, where each end is in the same format as `parse_duration`
+/// (e.g. "90", "1m..2m30s" or "..45s").
+pub fn parse_duration_range(value: &str) -> Result<ValueRange<f32>> {
+    parse_range(value, parse_duration)
+        .chain_err(|| format!("'{}' is not a valid range of durations", value))
+}
+
+/// Parses either a single value or an inclusive range of values separated by "..", where either
+/// end of the range can be left open.
+fn parse_range<T, F>(value: &str, parse: F) -> Result<ValueRange<T>>
+where
+    T: PartialOrd + Copy,
+    F: Fn(&str) -> Result<T>,
+{
+    let parse_end = |end: &str| -> Result<Option<T>> {
+        match end.trim() {
+            "" => Ok(None),
+            end => parse(end).map(Some),
+        }
+    };
+
+    let mut ends = value.splitn(2, "..");
+    let min = parse_end(ends.next().unwrap_or_default())?;
+
+    match ends.next() {
+        Some(max) => match (min, parse_end(max)?) {
+            (Some(min), Some(max)) if min > max => {
+                bail!("the start of the range is after the end")
+            }
+            (min, max) => Ok(ValueRange { min, max }),
+        },
+        None => match min {
+            Some(value) => Ok(ValueRange::exactly(value)),
+            None => bail!("expected a value or a range"),
+        },
+    }
+}
+
+/// Parses a plain number.
+fn parse_number<T: FromStr>(value: &str) -> Result<T> {
+    match value.parse() {
+        Ok(number) => Ok(number),
+        Err(_) => bail!("'{}' is not a number", value),
+    }
+}
+
+/// Parses an absolute or relative time, in the local time zone.
+///
+/// A date on its own is taken as the start of that day, or the last second of that day if
+/// `end_of_day` is set.
+fn parse_time(value: &str, end_of_day: bool) -> Result<DateTime<Local>> {
+    if let Some(time) = parse_relative_time(value)? {
+        return Ok(time);
+    }
+
+    let datetime = if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M") {
+        datetime
+    } else if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let datetime = if end_of_day {
+            date.and_hms_opt(23, 59, 59)
+        } else {
+            date.and_hms_opt(0, 0, 0)
+        };
+        match datetime {
+            Some(datetime) => datetime,
+            None => bail!("date is out of range"),
+        }
+    } else {
+        bail!("expected a date such as 2019-03-01 or a relative time such as 30d");
+    };
+
+    match Local.from_local_datetime(&datetime).earliest() {
+        Some(datetime) => Ok(datetime),
+        None => bail!("time does not exist in the local time zone"),
+    }
+}
+
+/// Parses a time relative to now, such as "12h", "30d" or "2w", which is `None` if the value isn't
+/// a relative time.
+fn parse_relative_time(value: &str) -> Result<Option<DateTime<Local>>> {
+    let unit = match value.chars().last() {
+        Some(unit @ ('h' | 'd' | 'w')) => unit,
+        _ => return Ok(None),
+    };
+    let amount: i64 = match value[..value.len() - unit.len_utf8()].parse() {
+        Ok(amount) => amount,
+        Err(_) => return Ok(None),
+    };
+    if amount <= 0 {
+        bail!("relative time must be positive");
+    }
+
+    let duration = match unit {
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        _ => Duration::try_weeks(amount),
+    };
+
+    match duration.and_then(|duration| Local::now().checked_sub_signed(duration)) {
+        Some(time) => Ok(Some(time)),
+        None => bail!("relative time is out of range"),
+    }
+}
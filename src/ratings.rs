@@ -0,0 +1,284 @@
+use crate::MatchedReplay;
+use spyparty::Replay;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// The rating every player starts with.
+const INITIAL_RATING: f64 = 1500.0;
+/// The maximum change in an Elo rating after a single game.
+const ELO_K_FACTOR: f64 = 32.0;
+/// The rating deviation every player starts with in Glicko-2.
+const GLICKO_INITIAL_DEVIATION: f64 = 350.0;
+/// The volatility every player starts with in Glicko-2.
+const GLICKO_INITIAL_VOLATILITY: f64 = 0.06;
+/// The Glicko-2 system constant, which limits how quickly the volatility can change.
+const GLICKO_TAU: f64 = 0.5;
+/// The factor for converting between the Glicko and Glicko-2 scales.
+const GLICKO_SCALE: f64 = 173.7178;
+
+/// The different systems players can be rated with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RatingSystem {
+    /// Elo, where a rating is a single number.
+    Elo,
+    /// Glicko-2, where a rating also has a deviation showing how reliable it is. Each game is
+    /// treated as its own rating period.
+    Glicko2,
+}
+
+/// A rating of a player in a particular role.
+#[derive(Clone, Copy, Debug)]
+pub struct Rating {
+    /// The rating itself.
+    pub value: f64,
+    /// How uncertain the rating is (only used by Glicko-2).
+    pub deviation: f64,
+    /// How erratic the player's results are (only used by Glicko-2).
+    pub volatility: f64,
+    /// The number of games the rating is based on.
+    pub games: u32,
+}
+
+/// The ratings of a single player.
+#[derive(Clone, Debug)]
+pub struct PlayerRatings {
+    pub user_name: String,
+    pub display_name: String,
+    /// The rating from games played as the spy, against the sniper ratings of opponents.
+    pub spy: Rating,
+    /// The rating from games played as the sniper, against the spy ratings of opponents.
+    pub sniper: Rating,
+    /// The rating from all games, regardless of role.
+    pub combined: Rating,
+}
+
+/// The ratings of both players after a single game.
+#[derive(Clone, Debug)]
+pub struct RatingChange {
+    pub start_time: u32,
+    pub path: String,
+    pub spy: String,
+    pub sniper: String,
+    pub spy_won: bool,
+    /// The spy rating of the spy after the game.
+    pub spy_rating: Rating,
+    /// The sniper rating of the sniper after the game.
+    pub sniper_rating: Rating,
+    /// The combined rating of the spy after the game.
+    pub spy_combined_rating: Rating,
+    /// The combined rating of the sniper after the game.
+    pub sniper_combined_rating: Rating,
+}
+
+/// The ratings of every player in a set of replays, along with how they changed after each game.
+pub struct Ratings {
+    pub system: RatingSystem,
+    pub history: Vec<RatingChange>,
+    players: HashMap<String, PlayerRatings>,
+}
+
+impl RatingSystem {
+    /// Gets the rating a player starts with.
+    fn initial(self) -> Rating {
+        Rating {
+            value: INITIAL_RATING,
+            deviation: match self {
+                RatingSystem::Elo => 0.0,
+                RatingSystem::Glicko2 => GLICKO_INITIAL_DEVIATION,
+            },
+            volatility: GLICKO_INITIAL_VOLATILITY,
+            games: 0,
+        }
+    }
+
+    /// Updates the ratings of two players after a game between them.
+    fn update(self, winner: &mut Rating, loser: &mut Rating) {
+        let (new_winner, new_loser) = match self {
+            RatingSystem::Elo => (elo(winner, loser, 1.0), elo(loser, winner, 0.0)),
+            RatingSystem::Glicko2 => (glicko2(winner, loser, 1.0), glicko2(loser, winner, 0.0)),
+        };
+
+        *winner = new_winner;
+        *loser = new_loser;
+    }
+}
+
+impl Ratings {
+    /// Calculates the ratings of every player by going through the replays in order.
+    ///
+    /// The replays should already be sorted by start time. Unfinished games are ignored.
+    pub fn calculate(replays: &[MatchedReplay], system: RatingSystem) -> Self {
+        let mut ratings = Ratings {
+            system,
+            history: Vec::new(),
+            players: HashMap::new(),
+        };
+
+        for replay in replays {
+            ratings.add_game(&replay.inner, &replay.path);
+        }
+
+        ratings
+    }
+
+    /// Gets the ratings of a player, by their user name.
+    pub fn get(&self, user_name: &str) -> Option<&PlayerRatings> {
+        self.players.get(user_name)
+    }
+
+    /// Gets the ratings of every player, sorted from the highest combined rating to the lowest.
+    pub fn leaderboard(&self) -> Vec<&PlayerRatings> {
+        let mut players: Vec<_> = self.players.values().collect();
+
+        players.sort_by(|a, b| {
+            b.combined
+                .value
+                .total_cmp(&a.combined.value)
+                .then(a.display_name.cmp(&b.display_name))
+        });
+
+        players
+    }
+
+    /// Updates the ratings of the spy and sniper of a game.
+    fn add_game(&mut self, replay: &Replay, path: &str) {
+        let spy_won = if replay.is_spy_win() {
+            true
+        } else if replay.is_sniper_win() {
+            false
+        } else {
+            return;
+        };
+
+        let header = &replay.header;
+
+        // Practice games against yourself say nothing about how good you are
+        if header.spy_user_name == header.sniper_user_name {
+            return;
+        }
+
+        let mut spy = self.player(&header.spy_user_name, replay.spy_name());
+        let mut sniper = self.player(&header.sniper_user_name, replay.sniper_name());
+
+        if spy_won {
+            self.system.update(&mut spy.spy, &mut sniper.sniper);
+            self.system.update(&mut spy.combined, &mut sniper.combined);
+        } else {
+            self.system.update(&mut sniper.sniper, &mut spy.spy);
+            self.system.update(&mut sniper.combined, &mut spy.combined);
+        }
+
+        self.history.push(RatingChange {
+            start_time: header.start_time,
+            path: path.to_string(),
+            spy: spy.display_name.clone(),
+            sniper: sniper.display_name.clone(),
+            spy_won,
+            spy_rating: spy.spy,
+            sniper_rating: sniper.sniper,
+            spy_combined_rating: spy.combined,
+            sniper_combined_rating: sniper.combined,
+        });
+
+        self.players.insert(spy.user_name.clone(), spy);
+        self.players.insert(sniper.user_name.clone(), sniper);
+    }
+
+    /// Gets a copy of the current ratings of a player, or their initial ratings if they haven't
+    /// played yet. The display name is updated to the latest one seen.
+    fn player(&self, user_name: &str, display_name: &str) -> PlayerRatings {
+        let initial = self.system.initial();
+        let mut player = self
+            .players
+            .get(user_name)
+            .cloned()
+            .unwrap_or_else(|| PlayerRatings {
+                user_name: user_name.to_string(),
+                display_name: String::new(),
+                spy: initial,
+                sniper: initial,
+                combined: initial,
+            });
+
+        player.display_name = display_name.to_string();
+        player
+    }
+}
+
+/// Calculates the new Elo rating of a player after a game, where the score is 1 for a win and 0
+/// for a loss.
+fn elo(player: &Rating, opponent: &Rating, score: f64) -> Rating {
+    let expected = 1.0 / (1.0 + 10f64.powf((opponent.value - player.value) / 400.0));
+
+    Rating {
+        value: player.value + ELO_K_FACTOR * (score - expected),
+        games: player.games + 1,
+        ..*player
+    }
+}
+
+/// Calculates the new Glicko-2 rating of a player after a game, where the score is 1 for a win
+/// and 0 for a loss.
+///
+/// This follows the steps in Mark Glickman's "Example of the Glicko-2 system", with a rating
+/// period containing a single game.
+fn glicko2(player: &Rating, opponent: &Rating, score: f64) -> Rating {
+    let mu = (player.value - INITIAL_RATING) / GLICKO_SCALE;
+    let phi = player.deviation / GLICKO_SCALE;
+    let opponent_mu = (opponent.value - INITIAL_RATING) / GLICKO_SCALE;
+    let opponent_phi = opponent.deviation / GLICKO_SCALE;
+
+    let g = 1.0 / (1.0 + 3.0 * opponent_phi.powi(2) / PI.powi(2)).sqrt();
+    let expected = 1.0 / (1.0 + (-g * (mu - opponent_mu)).exp());
+    let variance = 1.0 / (g.powi(2) * expected * (1.0 - expected));
+    let delta = variance * g * (score - expected);
+
+    // Find the new volatility using the Illinois algorithm
+    let a = player.volatility.powi(2).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        ex * (delta.powi(2) - phi.powi(2) - variance - ex)
+            / (2.0 * (phi.powi(2) + variance + ex).powi(2))
+            - (x - a) / GLICKO_TAU.powi(2)
+    };
+
+    let mut lower = a;
+    let mut upper = if delta.powi(2) > phi.powi(2) + variance {
+        (delta.powi(2) - phi.powi(2) - variance).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * GLICKO_TAU) < 0.0 {
+            k += 1.0;
+        }
+        a - k * GLICKO_TAU
+    };
+    let mut f_lower = f(lower);
+    let mut f_upper = f(upper);
+
+    while (upper - lower).abs() > 0.000_001 {
+        let new = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+        let f_new = f(new);
+
+        if f_new * f_upper <= 0.0 {
+            lower = upper;
+            f_lower = f_upper;
+        } else {
+            f_lower /= 2.0;
+        }
+
+        upper = new;
+        f_upper = f_new;
+    }
+
+    let volatility = (lower / 2.0).exp();
+    let pre_phi = (phi.powi(2) + volatility.powi(2)).sqrt();
+    let new_phi = 1.0 / (1.0 / pre_phi.powi(2) + 1.0 / variance).sqrt();
+    let new_mu = mu + new_phi.powi(2) * g * (score - expected);
+
+    Rating {
+        value: new_mu * GLICKO_SCALE + INITIAL_RATING,
+        deviation: new_phi * GLICKO_SCALE,
+        volatility,
+        games: player.games + 1,
+    }
+}
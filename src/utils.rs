@@ -1,24 +1,143 @@
-use spyparty::Replay;
+use chrono::{DateTime, Local, TimeZone};
+use regex::Regex;
+use spyparty::{GameMode, MapVariant, Replay};
+use std::iter;
 
-/// Checks if a replay has a particular player, automatically checking if the Steam version of
-/// the name as well.
+/// Checks if a replay has a particular player, as either the spy or the sniper.
 pub fn has_player(replay: &Replay, player: &str) -> bool {
-    replay.has_player(player) || replay.has_player(&[player, "/steam"].join(""))
+    has_spy(replay, player) || has_sniper(replay, player)
 }
 
-/// Checks if a replay has a particular spy, automatically checking if the Steam version of
-/// the name as well.
+/// Checks if a replay has a particular spy, matching the player against both the user name and
+/// display name of the spy (see `name_matches`).
 pub fn has_spy(replay: &Replay, player: &str) -> bool {
-    replay.has_spy(player) || replay.has_spy(&[player, "/steam"].join(""))
+    spy_names(replay).any(|name| name_matches(name, player))
 }
 
-/// Checks if a replay has a particular sniper, automatically checking if the Steam version of
-/// the name as well.
+/// Checks if a replay has a particular sniper, matching the player against both the user name
+/// and display name of the sniper (see `name_matches`).
 pub fn has_sniper(replay: &Replay, player: &str) -> bool {
-    replay.has_sniper(player) || replay.has_sniper(&[player, "/steam"].join(""))
+    sniper_names(replay).any(|name| name_matches(name, player))
+}
+
+/// Checks if a regular expression matches the user name or display name of the spy in a replay.
+pub fn spy_matches(replay: &Replay, regex: &Regex) -> bool {
+    spy_names(replay).any(|name| regex.is_match(name))
+}
+
+/// Checks if a regular expression matches the user name or display name of the sniper in a
+/// replay.
+pub fn sniper_matches(replay: &Replay, regex: &Regex) -> bool {
+    sniper_names(replay).any(|name| regex.is_match(name))
+}
+
+/// Gets the user name of the spy, followed by their display name if they have one.
+fn spy_names(replay: &Replay) -> impl Iterator<Item = &str> {
+    let header = &replay.header;
+    iter::once(header.spy_user_name.as_str()).chain(header.spy_display_name.as_deref())
+}
+
+/// Gets the user name of the sniper, followed by their display name if they have one.
+fn sniper_names(replay: &Replay) -> impl Iterator<Item = &str> {
+    let header = &replay.header;
+    iter::once(header.sniper_user_name.as_str()).chain(header.sniper_display_name.as_deref())
+}
+
+/// Checks if a name matches a player's name given as a filter, ignoring case.
+///
+/// The player can be a glob pattern, where `*` matches any number of characters and `?` matches
+/// exactly one. The Steam version of a name (ending in "/steam") is matched as well.
+pub fn name_matches(name: &str, player: &str) -> bool {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let pattern: Vec<char> = player.to_lowercase().chars().collect();
+    let steam: Vec<char> = "/steam".chars().collect();
+
+    glob_matches(&name, &pattern)
+        || (name.ends_with(&steam) && glob_matches(&name[..name.len() - steam.len()], &pattern))
+}
+
+/// Gets a name to compare user names by, ignoring case and the "/steam" suffix.
+pub fn name_key(user_name: &str) -> String {
+    let user_name = user_name.to_lowercase();
+
+    match user_name.strip_suffix("/steam") {
+        Some(user_name) => user_name.to_string(),
+        None => user_name,
+    }
+}
+
+/// Checks if a player's name given as a filter is a glob pattern.
+pub fn is_glob(name: &str) -> bool {
+    name.contains(['*', '?'])
+}
+
+/// Matches text against a glob pattern of `*` and `?` wildcards, backtracking to the last `*`
+/// whenever the rest of the pattern doesn't match.
+fn glob_matches(text: &[char], pattern: &[char]) -> bool {
+    let mut t = 0;
+    let mut p = 0;
+    let mut last_star = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                last_star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                t += 1;
+                p += 1;
+            }
+            _ => match last_star {
+                // Let the last star swallow one more character and try again
+                Some((star, start)) => {
+                    last_star = Some((star, start + 1));
+                    p = star + 1;
+                    t = start + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Calculates the fraction of the starting clock that was used in a replay, if the starting clock
+/// is known.
+pub fn clock_usage(replay: &Replay) -> Option<f32> {
+    replay
+        .header
+        .result_data
+        .clock_start
+        .map(|clock_start| replay.header.duration / clock_start as f32)
 }
 
 /// Calculates the percentage of a value out of a total.
 pub fn percentage(value: u32, total: u32) -> f32 {
     (value as f32 / total as f32) * 100f32
 }
+
+/// Gets the time a replay started, in the local time zone.
+pub fn start_time(replay: &Replay) -> DateTime<Local> {
+    Local
+        .timestamp_opt(i64::from(replay.header.start_time), 0)
+        .unwrap()
+}
+
+/// Converts a game mode into its short version.
+pub fn mode_to_string(mode: &GameMode) -> String {
+    match mode {
+        GameMode::Known(x) => format!("k{}", x),
+        GameMode::Pick(x, y) => format!("p{}/{}", x, y),
+        GameMode::Any(x, y) => format!("a{}/{}", x, y),
+    }
+}
+
+/// Converts a map variant into a string.
+pub fn map_variant_to_string(variant: &MapVariant) -> String {
+    match variant {
+        MapVariant::Teien(variant) => format!("{:?}", variant),
+        MapVariant::None => "None".to_string(),
+    }
+}
@@ -0,0 +1,610 @@
+use chrono::{Local, TimeZone};
+use spyparsey::index::PARSER_VERSION;
+use spyparsey::organize::{self, Action};
+use spyparsey::output::group_by::{self, Dimension};
+use spyparsey::output::head_to_head::{HeadToHead, Role};
+use spyparsey::output::json::ReplayRecord;
+use spyparsey::output::lines::LineFormat;
+use spyparsey::output::scout;
+use spyparsey::output::sheet::{self, Template};
+use spyparsey::output::summary::{summarize, SectionStats, SummaryFormat, SummaryOptions};
+use spyparsey::output::timeline::{self, Period};
+use spyparsey::query::{
+    parse_clock, parse_clock_usage, parse_duration, parse_player_regex, parse_since, parse_until,
+};
+use spyparsey::{form, Aliases, Expression, Index, MatchResult, Query, RatingSystem, Ratings};
+use spyparty::Map;
+
+fn count(query: &Query) -> usize {
+    spyparsey::search(vec!["tests/replays"], query)
+        .unwrap()
+        .replays
+        .len()
+}
+
+#[test]
+fn empty_query_matches_everything() {
+    assert_eq!(count(&Query::new()), 7);
+}
+
+#[test]
+fn players() {
+    assert_eq!(count(&Query::new().players(vec!["checker"])), 2);
+}
+
+#[test]
+fn player_patterns() {
+    assert_eq!(count(&Query::new().players(vec!["CHECKER"])), 2);
+    assert_eq!(count(&Query::new().players(vec!["check*"])), 3);
+    assert_eq!(count(&Query::new().spies(vec!["?azybear"])), 1);
+
+    // Display names are matched as well as user names
+    assert_eq!(count(&Query::new().snipers(vec!["checker test*"])), 1);
+
+    let regex = parse_player_regex("^(lazy|canadian)").unwrap();
+    assert_eq!(count(&Query::new().players_regex(vec![regex])), 3);
+    assert!(parse_player_regex("(checker").is_err());
+}
+
+#[test]
+fn pair() {
+    assert_eq!(count(&Query::new().pair("checker", "lazybear")), 2);
+}
+
+#[test]
+fn aliases() {
+    let aliases = Aliases::parse(r#"both = ["checker", "lazybear"]"#).unwrap();
+    let collection = spyparsey::search(
+        vec!["tests/replays"],
+        &Query::new().aliases(aliases).players(vec!["checker"]),
+    )
+    .unwrap();
+
+    assert_eq!(collection.replays.len(), 2);
+    for replay in &collection.replays {
+        assert_eq!(replay.inner.header.spy_user_name, "both");
+        assert_eq!(replay.inner.header.sniper_user_name, "both");
+    }
+
+    assert!(Aliases::parse(
+        r#"a = ["checker"]
+b = ["checker"]"#
+    )
+    .is_err());
+
+    // User names are matched ignoring case and "/steam", and globs match the aliased user names
+    let aliases = || Aliases::parse(r#"main = ["Checker/steam"]"#).unwrap();
+    let aliased = |query: Query| count(&Query::new().aliases(aliases()).exclude(query));
+    assert_eq!(aliased(Query::new().players(vec!["CHECKER"])), 5);
+    assert_eq!(aliased(Query::new().players(vec!["check*"])), 4);
+
+    let query = || Query::new().aliases(aliases());
+    assert_eq!(count(&query().players(vec!["Checker"])), 2);
+    assert_eq!(count(&query().players(vec!["check*"])), 3);
+    assert_eq!(count(&query().pair("check*", "lazybear")), 2);
+    let expression = Expression::parse("spy:check* or sniper:check*").unwrap();
+    assert_eq!(count(&query().expression(expression)), 3);
+
+    // A glob matching several identities resolves to them in the same order every time
+    let aliases = Aliases::parse(
+        r#"zed = ["player-a"]
+ann = ["player-b"]
+max = ["player-c"]"#,
+    )
+    .unwrap();
+    let query = Query::new().aliases(aliases).players(vec!["player-*"]);
+    assert_eq!(query.players, vec!["player-*", "ann", "max", "zed"]);
+}
+
+#[test]
+fn maps() {
+    assert_eq!(count(&Query::new().maps(vec![Map::Teien])), 3);
+}
+
+#[test]
+fn expression() {
+    let expression = Expression::parse("player:checker and map:teien").unwrap();
+    assert_eq!(count(&Query::new().expression(expression)), 2);
+
+    let expression = Expression::parse("map:teien and not (player:checker)").unwrap();
+    assert_eq!(count(&Query::new().expression(expression)), 1);
+}
+
+#[test]
+fn invalid_expressions() {
+    assert!(Expression::parse("(map:teien").is_err());
+    assert!(Expression::parse("map:teien or").is_err());
+    assert!(Expression::parse("colour:red").is_err());
+    assert!(Expression::parse(&format!("{}map:teien", "not ".repeat(100))).is_ok());
+    assert!(Expression::parse(&"not ".repeat(100_000)).is_err());
+    assert!(Expression::parse(&"(".repeat(100_000)).is_err());
+}
+
+#[test]
+fn times() {
+    assert!(parse_since("30d").is_ok());
+    assert!(parse_since("-5d").is_err());
+    assert!(parse_since("0h").is_err());
+    assert!(parse_since("99999999d").is_err());
+    assert!(parse_since("9999999999999d").is_err());
+    assert!(parse_until("2019-13-01").is_err());
+
+    // A date on its own includes every game played on that day
+    let collection = spyparsey::search(vec!["tests/replays"], &Query::new()).unwrap();
+    let start_time = Local
+        .timestamp_opt(i64::from(collection.replays[0].inner.header.start_time), 0)
+        .unwrap();
+    let until = parse_until(&start_time.format("%Y-%m-%d").to_string()).unwrap();
+    assert!(until >= start_time);
+
+    let query = Query::new().until(start_time);
+    assert!(count(&query) >= 1);
+}
+
+#[test]
+fn ranges() {
+    assert!(parse_clock("180..240").is_ok());
+    assert!(parse_clock("240..").is_ok());
+    assert!(parse_clock("240..180").is_err());
+    assert!(parse_clock_usage("0.9..0.5").is_err());
+    assert_eq!(parse_duration("1m30s").unwrap(), 90.0);
+    assert!(parse_duration("-90").is_err());
+    assert!(parse_duration("0").is_err());
+    assert!(parse_duration("NaN").is_err());
+    assert!(parse_duration("inf").is_err());
+}
+
+#[test]
+fn exclude() {
+    let query = Query::new().exclude(Query::new().maps(vec![Map::Teien]));
+    assert_eq!(count(&query), 4);
+
+    let query = Query::new()
+        .maps(vec![Map::Teien])
+        .exclude(Query::new().players(vec!["checker"]));
+    assert_eq!(count(&query), 1);
+}
+
+#[test]
+fn json_records() {
+    let collection = spyparsey::search(vec!["tests/replays"], &Query::new()).unwrap();
+
+    for replay in &collection.replays {
+        let record = ReplayRecord::new(replay);
+        let header = &replay.inner.header;
+
+        assert_eq!(record.path, replay.path);
+        assert_eq!(record.spy_display_name, header.spy_display_name.as_deref());
+        assert_eq!(
+            record.result_data.completed_missions.len(),
+            header.result_data.completed_missions.len()
+        );
+
+        match record.winner_role {
+            Some("spy") => assert_eq!(record.winner.as_ref(), Some(replay.inner.spy_name())),
+            Some("sniper") => assert_eq!(record.winner.as_ref(), Some(replay.inner.sniper_name())),
+            _ => assert!(!replay.inner.is_spy_win() && !replay.inner.is_sniper_win()),
+        }
+    }
+}
+
+#[test]
+fn summary() {
+    let query = Query::new();
+    let collection = spyparsey::search(vec!["tests/replays"], &query).unwrap();
+    let summary = summarize(&collection.replays, &query, Default::default());
+
+    assert_eq!(summary.total, 7);
+
+    let maps = summary
+        .sections
+        .iter()
+        .find(|section| section.key == "maps")
+        .unwrap();
+
+    match &maps.stats {
+        SectionStats::Counts(stats) => {
+            assert_eq!(stats.iter().map(|stat| stat.count).sum::<u32>(), 7);
+        }
+        _ => panic!("map stats should be counts"),
+    }
+
+    let map_wins = summary
+        .sections
+        .iter()
+        .find(|section| section.key == "map_wins")
+        .unwrap();
+
+    match &map_wins.stats {
+        SectionStats::MapWins(stats) => {
+            assert_eq!(stats.iter().map(|stat| stat.games).sum::<u32>(), 7);
+
+            for stat in stats {
+                let results = stat.results.iter().map(|result| result.count).sum::<u32>();
+                assert_eq!(results, stat.games);
+                assert!(stat.spy_wins + stat.sniper_wins <= stat.games);
+                assert!(stat.players.is_empty());
+            }
+        }
+        _ => panic!("map win stats should be map wins"),
+    }
+}
+
+#[test]
+fn mission_breakdowns() {
+    let query = Query::new();
+    let collection = spyparsey::search(vec!["tests/replays"], &query).unwrap();
+    let summary = summarize(&collection.replays, &query, Default::default());
+
+    assert!(summary
+        .sections
+        .iter()
+        .all(|section| section.key != "missions_completed_by_map"));
+
+    let options = SummaryOptions {
+        detailed: true,
+        ..Default::default()
+    };
+    let summary = summarize(&collection.replays, &query, options);
+
+    let section = |key: &str| {
+        &summary
+            .sections
+            .iter()
+            .find(|section| section.key == key)
+            .unwrap()
+            .stats
+    };
+
+    for &key in &["missions_completed_by_map", "missions_completed_by_mode"] {
+        match section(key) {
+            SectionStats::Groups(groups) => {
+                assert_eq!(groups.iter().map(|group| group.games).sum::<u32>(), 7);
+
+                for group in groups {
+                    for stat in &group.stats {
+                        assert!(stat.count <= group.games);
+                    }
+                }
+            }
+            _ => panic!("mission breakdowns should be groups"),
+        }
+    }
+
+    match (
+        section("missions_completed"),
+        section("missions_never_completed"),
+    ) {
+        (SectionStats::Counts(completed), SectionStats::Counts(never_completed)) => {
+            for stat in never_completed {
+                assert!(completed.iter().all(|other| other.name != stat.name));
+            }
+        }
+        _ => panic!("mission stats should be counts"),
+    }
+}
+
+#[test]
+fn mission_set_win_rates() {
+    let query = Query::new();
+    let collection = spyparsey::search(vec!["tests/replays"], &query).unwrap();
+    let options = SummaryOptions {
+        mission_sets: 1,
+        detailed: true,
+    };
+    let summary = summarize(&collection.replays, &query, options);
+
+    let mission_sets = summary
+        .sections
+        .iter()
+        .find(|section| section.key == "mission_set_win_rates")
+        .unwrap();
+
+    match &mission_sets.stats {
+        SectionStats::MissionSets(stats) => {
+            assert_eq!(stats.len(), 1);
+            assert!(stats[0].spy_wins + stats[0].spy_shots <= stats[0].games);
+        }
+        _ => panic!("mission set win rates should be mission sets"),
+    }
+}
+
+#[test]
+fn form() {
+    let query = Query::new().players(vec!["checker".to_string()]);
+    let collection = spyparsey::search(vec!["tests/replays"], &query).unwrap();
+    let forms = form::calculate(&collection.replays, &["checker", "nobody"], 3);
+
+    assert_eq!(forms.len(), 1);
+
+    let checker = &forms[0];
+    assert_eq!(
+        checker.overall.games(),
+        checker.spy.games() + checker.sniper.games()
+    );
+
+    for role in &[&checker.spy, &checker.sniper, &checker.overall] {
+        assert!(role.recent_games() <= 3);
+        assert!(role.longest_win_streak <= role.wins);
+        assert!(role.longest_loss_streak <= role.losses);
+        assert!(
+            role.current_streak.unsigned_abs()
+                <= role.longest_win_streak.max(role.longest_loss_streak)
+        );
+    }
+}
+
+#[test]
+fn scout() {
+    let query = Query::new().players(vec!["checker"]);
+    let collection = spyparsey::search(vec!["tests/replays"], &query).unwrap();
+    let report = scout::scout(&collection.replays, &query, "checker");
+
+    assert_eq!(report.total, collection.replays.len());
+
+    let section = |key: &str| {
+        &report
+            .sections
+            .iter()
+            .find(|section| section.key == key)
+            .unwrap()
+            .stats
+    };
+
+    // Each role only counts the games checker played in that role
+    for &key in &["spy_record", "sniper_record"] {
+        match section(key) {
+            SectionStats::Players(stats) => {
+                assert_eq!(stats.len(), 1);
+                assert_eq!(stats[0].user_name, "checker");
+                assert_eq!(stats[0].wins + stats[0].losses, 1);
+            }
+            _ => panic!("{} should be player stats", key),
+        }
+    }
+
+    match section("spy_maps") {
+        SectionStats::Counts(stats) => {
+            assert_eq!(stats.iter().map(|stat| stat.count).sum::<u32>(), 1);
+        }
+        _ => panic!("spy maps should be counts"),
+    }
+
+    match section("form") {
+        SectionStats::Form(stats) => assert_eq!(stats.len(), 3),
+        _ => panic!("form should be form stats"),
+    }
+
+    // A glob pattern has to match a single player
+    let query = Query::new().players(vec!["*"]);
+    let collection = spyparsey::search(vec!["tests/replays"], &query).unwrap();
+    assert!(scout::show(&collection.replays, &query, SummaryFormat::Text).is_err());
+}
+
+#[test]
+fn timeline() {
+    let query = Query::new();
+    let collection = spyparsey::search(vec!["tests/replays"], &query).unwrap();
+
+    for &period in &[Period::Day, Period::Week, Period::Month] {
+        let periods = timeline::periods(&collection.replays, period);
+
+        assert_eq!(periods.values().map(|stats| stats.games).sum::<u32>(), 7);
+
+        for stats in periods.values() {
+            assert_eq!(stats.results.values().sum::<u32>(), stats.games);
+            assert!(stats.spy_wins <= stats.games);
+        }
+    }
+
+    let days = timeline::periods(&collection.replays, Period::Day);
+    let months = timeline::periods(&collection.replays, Period::Month);
+    assert!(months.len() <= days.len());
+}
+
+#[test]
+fn head_to_head() {
+    let players = vec!["checker".to_string(), "lazybear".to_string()];
+    let query = Query::new().players(players.clone());
+    let collection = spyparsey::search(vec!["tests/replays"], &query).unwrap();
+    let head_to_head = HeadToHead::new(&players, &collection.replays);
+
+    let (checker_wins, checker_losses) = head_to_head.record(0, 1).get(Role::Both);
+    let (lazybear_wins, lazybear_losses) = head_to_head.record(1, 0).get(Role::Both);
+
+    assert_eq!(checker_wins, lazybear_losses);
+    assert_eq!(checker_losses, lazybear_wins);
+    assert!(checker_wins + checker_losses <= 2);
+}
+
+#[test]
+fn ratings() {
+    let collection = spyparsey::search(vec!["tests/replays"], &Query::new()).unwrap();
+
+    let elo = Ratings::calculate(&collection.replays, RatingSystem::Elo);
+    let leaderboard = elo.leaderboard();
+    let total: f64 = leaderboard.iter().map(|player| player.combined.value).sum();
+
+    // Elo is zero-sum, so the average rating never changes
+    assert!((total - 1500.0 * leaderboard.len() as f64).abs() < 0.001);
+
+    let glicko = Ratings::calculate(&collection.replays, RatingSystem::Glicko2);
+    let games: u32 = glicko
+        .leaderboard()
+        .iter()
+        .map(|player| player.combined.games)
+        .sum();
+
+    assert_eq!(games as usize, glicko.history.len() * 2);
+
+    for change in &glicko.history {
+        let winner = if change.spy_won {
+            change.spy_combined_rating
+        } else {
+            change.sniper_combined_rating
+        };
+        assert!(winner.deviation < 350.0);
+    }
+}
+
+#[test]
+fn matches() {
+    let collection = spyparsey::search(vec!["tests/replays"], &Query::new()).unwrap();
+    let matches = spyparsey::matches::group(&collection.replays);
+    let games: usize = matches
+        .iter()
+        .map(|game_match| game_match.games.len())
+        .sum();
+
+    assert_eq!(games, collection.replays.len());
+
+    for game_match in &matches {
+        for game in &game_match.games {
+            assert!(game.inner.has_player(&game_match.players[0]));
+            assert!(game.inner.has_player(&game_match.players[1]));
+        }
+    }
+
+    let total = count(&Query::new().players(vec!["checker"]));
+    let results = [MatchResult::Win, MatchResult::Loss, MatchResult::Draw]
+        .iter()
+        .map(|&result| count(&Query::new().players(vec!["checker"]).match_result(result)))
+        .sum::<usize>();
+
+    assert_eq!(results, total);
+
+    // Whole matches are kept even when the other filters only match some of their games
+    let query = Query::new().players(vec!["checker"]).spies(vec!["checker"]);
+    assert_eq!(count(&query), 1);
+    assert_eq!(count(&query.whole_matches()), 2);
+}
+
+#[test]
+fn index() {
+    // The index has to be rebuilt whenever the parser changes
+    let manifest = include_str!("../Cargo.toml");
+    assert!(manifest.contains(&format!("spyparty = \"={}\"", PARSER_VERSION)));
+
+    let root = std::env::temp_dir().join(format!("spyparsey-index-{}", std::process::id()));
+    let replays = root.join("replays");
+    let replay = replays.join("4.replay");
+    let index_path = root.join("index");
+    std::fs::create_dir_all(&replays).unwrap();
+    std::fs::copy("tests/replays/4.replay", &replay).unwrap();
+
+    let mut index = Index::load(&index_path);
+    spyparsey::parse_and_filter_replays(vec![replay.clone()], &Query::new(), Some(&mut index))
+        .unwrap();
+    index.save().unwrap();
+    assert_eq!(Index::load(&index_path).len(), 1);
+
+    // Replays which have been deleted or moved are only removed from under the searched paths
+    std::fs::remove_file(&replay).unwrap();
+    let mut index = Index::load(&index_path);
+    index.prune(&["tests/replays"]);
+    assert_eq!(index.len(), 1);
+    index.prune(&[&replays]);
+    index.save().unwrap();
+    assert!(Index::load(&index_path).is_empty());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn group_by() {
+    let collection = spyparsey::search(vec!["tests/replays"], &Query::new()).unwrap();
+    let groups = group_by::group(&collection.replays, &[Dimension::Map, Dimension::Spy]);
+
+    assert_eq!(groups.values().map(|stats| stats.games).sum::<u32>(), 7);
+
+    let teien: u32 = groups
+        .iter()
+        .filter(|(values, _)| values[0] == Map::Teien.to_string())
+        .map(|(_, stats)| stats.games)
+        .sum();
+
+    assert_eq!(teien, 3);
+
+    // Players are grouped by user name, since display names can change
+    for replay in &collection.replays {
+        assert_eq!(
+            Dimension::Sniper.value(&replay.inner),
+            replay.inner.header.sniper_user_name
+        );
+    }
+}
+
+#[test]
+fn organize() {
+    let collection = spyparsey::search(vec!["tests/replays"], &Query::new()).unwrap();
+    let root = std::env::temp_dir().join(format!("spyparsey-organize-{}", std::process::id()));
+    let dimensions = [Dimension::Map, Dimension::Spy];
+
+    let organized =
+        organize::organize(&collection.replays, &root, &dimensions, Action::Copy).unwrap();
+    assert_eq!(organized, 7);
+    assert!(root
+        .join("Terrace/checker_thisisalongusername_test/4.replay")
+        .is_file());
+
+    // Replays which have already been organized are never overwritten
+    let organized =
+        organize::organize(&collection.replays, &root, &dimensions, Action::Copy).unwrap();
+    assert_eq!(organized, 0);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn sheet() {
+    let collection = spyparsey::search(vec!["tests/replays"], &Query::new()).unwrap();
+
+    // The default template has a table of games as the sniper, as the spy and of missions
+    let sheets = sheet::build(&collection.replays, "checker", &Template::default());
+    assert_eq!(sheets.len(), 3);
+    assert_eq!(sheets[0].header[0], "SNIPER");
+    assert_eq!(sheets[0].rows.len(), 1);
+    assert_eq!(sheets[1].rows[0][4], "L");
+    assert_eq!(sheets[2].header.len(), 9);
+
+    let template = Template::parse(
+        r#"
+        win = "Win"
+
+        [[tables]]
+        role = "both"
+        columns = ["role", { column = "result", label = "RESULT" }, "opponent"]
+        "#,
+    )
+    .unwrap();
+    let sheets = sheet::build(&collection.replays, "lazybear", &template);
+    assert_eq!(sheets[0].header, vec!["ROLE", "RESULT", "OPPONENT"]);
+    assert_eq!(sheets[0].rows.len(), 2);
+    assert!(sheets[0].rows.iter().all(|row| row[1] == "Win"));
+
+    assert!(Template::parse("[[tables]]\nrole = \"spy\"\ncolumns = [\"nope\"]").is_err());
+    assert!(Template::parse("[[tables]]\nrole = \"spy\"\ncolumns = []").is_err());
+}
+
+#[test]
+fn line_format() {
+    let query = Query::new().spies(vec!["checker"]).maps(vec![Map::Teien]);
+    let collection = spyparsey::search(vec!["tests/replays"], &query).unwrap();
+
+    let format = LineFormat::parse(
+        "{spy} vs {sniper} on {map}: {result}, {winner} won ({completed_missions:short}) {{{duration}}}",
+    )
+    .unwrap();
+    assert_eq!(
+        format.line(&collection.replays[0]),
+        "checker vs lazybear on Teien: Spy Shot, lazybear won (BB, Transfer MF) {1m20s}"
+    );
+
+    let format = LineFormat::parse("{start_time:%Y} {clock_usage:.1} {duration:.0}").unwrap();
+    assert_eq!(format.line(&collection.replays[0]), "2018 0.4 80");
+
+    assert!(LineFormat::parse("{nope}").is_err());
+    assert!(LineFormat::parse("{map:short}").is_err());
+    assert!(LineFormat::parse("{map").is_err());
+    assert!(LineFormat::parse("map}").is_err());
+}
@@ -54,3 +54,20 @@ test_snapshot!(
         "a4/8"
     ]
 );
+test_snapshot!(not_map, &["--not-map", "teien"]);
+test_snapshot!(summary_detailed, &["summary", "--detailed"]);
+test_snapshot!(
+    saved_query,
+    &[
+        "--config",
+        "tests/config.toml",
+        "--query",
+        "checker-vs-lazybear-on-teien"
+    ]
+);
+test_snapshot!(scout, &["scout", "checker"]);
+test_snapshot!(stats_group_by, &["stats", "group-by", "map", "result"]);
+test_snapshot!(
+    filter_before_subcommand,
+    &["--players", "checker", "lazybear", "query", "--count"]
+);